@@ -1,14 +1,34 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde::Deserialize;
 use shlex::{split, Shlex};
-use std::process::{Command, Stdio};
-use std::{fmt, str};
+use std::collections::VecDeque;
+use std::io::Read;
+#[cfg(target_family = "unix")]
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{fmt, str, thread};
 
+use crate::config::Settings;
+use crate::logger;
 use crate::shell::Shell;
+use crate::utils::strip_ansi_sequences;
 
-#[derive(Debug)]
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Time given to read the remaining output once the command has exited.
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
 pub struct CorrectedCommand {
     pub script: String,
     pub side_effect: Option<fn(CrabCommand, Option<&str>)>,
     pub priority: u16,
+    /// Name of the rule the command comes from.
+    pub rule_name: String,
+    /// Names of all the rules that proposed the same script, starting with
+    /// `rule_name`. Filled when the duplicated commands are removed.
+    pub rule_names: Vec<String>,
 }
 
 impl CorrectedCommand {
@@ -16,11 +36,23 @@ impl CorrectedCommand {
         script: String,
         side_effect: Option<fn(CrabCommand, Option<&str>)>,
         priority: u16,
+        rule_name: String,
     ) -> Self {
         Self {
             script,
             side_effect,
             priority,
+            rule_names: vec![rule_name.to_owned()],
+            rule_name,
+        }
+    }
+    /// Returns a copy of the command with a script edited by the user. The side
+    /// effect is dropped, it was written for the original script.
+    pub fn with_edited_script(&self, script: String) -> Self {
+        Self {
+            script,
+            side_effect: None,
+            ..self.clone()
         }
     }
     pub fn get_script(&self) -> &String {
@@ -34,11 +66,56 @@ impl CorrectedCommand {
     }
 }
 
-#[derive(Debug)]
+/// Serializes the command for the JSON output. The side effect itself cannot be
+/// serialized, only whether the command has one.
+impl Serialize for CorrectedCommand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CorrectedCommand", 5)?;
+        state.serialize_field("script", &self.script)?;
+        state.serialize_field("priority", &self.priority)?;
+        state.serialize_field("rule", &self.rule_name)?;
+        state.serialize_field("rules", &self.rule_names)?;
+        state.serialize_field("side_effect", &self.side_effect.is_some())?;
+        state.end()
+    }
+}
+
+/// Exit code of the POSIX shells when the command is found but cannot be executed.
+pub const EXIT_CODE_CANNOT_EXECUTE: i32 = 126;
+/// Exit code of the POSIX shells when the command is not found.
+pub const EXIT_CODE_NOT_FOUND: i32 = 127;
+
+/// Output stream of a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Text written by a command to one of its streams, before the command wrote to the
+/// other one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputChunk {
+    pub stream: Stream,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
 pub struct CrabCommand {
     pub script: String,
+    /// Merged view of the output: the stdout followed by the stderr.
     pub output: Option<String>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    /// The output in the order it was written, when the command was run by ohcrab.
+    /// Otherwise, the stdout followed by the stderr.
+    pub output_chunks: Vec<OutputChunk>,
     pub script_parts: Vec<String>,
+    /// Exit code of the command, `None` if it was not run or was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// Signal that terminated the command, if any.
+    pub signal: Option<i32>,
 }
 
 impl fmt::Display for CrabCommand {
@@ -72,28 +149,74 @@ fn concat_stdout_stderrr(stdout: Option<String>, stderr: Option<String>) -> Opti
 impl CrabCommand {
     pub fn new(script: String, stdout: Option<String>, stderr: Option<String>) -> Self {
         let split_parts = CrabCommand::split_command(&script);
-        let output = concat_stdout_stderrr(stdout, stderr);
+        let output = concat_stdout_stderrr(stdout.clone(), stderr.clone());
+        let output_chunks = [(Stream::Stdout, &stdout), (Stream::Stderr, &stderr)]
+            .into_iter()
+            .filter_map(|(stream, text)| match text {
+                Some(text) if !text.is_empty() => Some(OutputChunk {
+                    stream,
+                    text: text.to_owned(),
+                }),
+                _ => None,
+            })
+            .collect();
 
         CrabCommand {
             script,
             output,
+            stdout,
+            stderr,
+            output_chunks,
             script_parts: split_parts,
+            exit_code: None,
+            signal: None,
+        }
+    }
+
+    /// Sets how the command terminated.
+    pub fn with_exit_status(mut self, exit_code: Option<i32>, signal: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self.signal = signal;
+        self
+    }
+
+    /// Sets the order in which the output was written.
+    pub fn with_output_chunks(mut self, output_chunks: Vec<OutputChunk>) -> Self {
+        self.output_chunks = output_chunks;
+        self
+    }
+
+    /// Returns `true` if the command was run and exited successfully.
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Returns the output written to `stream`.
+    pub fn get_stream(&self, stream: Stream) -> Option<&String> {
+        match stream {
+            Stream::Stdout => self.stdout.as_ref(),
+            Stream::Stderr => self.stderr.as_ref(),
         }
     }
 
+    /// Returns a copy of the command with another script and, if one of them is given,
+    /// another output. The exit status is kept.
     pub fn update(
         &self,
         script: Option<String>,
         stdout: Option<String>,
         stderr: Option<String>,
     ) -> CrabCommand {
-        let output = concat_stdout_stderrr(stdout, stderr);
-
-        CrabCommand::new(
-            script.unwrap_or(self.script.to_owned()),
-            output.map_or(self.output.to_owned(), Some),
-            None,
-        )
+        let script = script.unwrap_or(self.script.to_owned());
+        if stdout.is_none() && stderr.is_none() {
+            CrabCommand {
+                script_parts: CrabCommand::split_command(&script),
+                script,
+                ..self.clone()
+            }
+        } else {
+            CrabCommand::new(script, stdout, stderr).with_exit_status(self.exit_code, self.signal)
+        }
     }
 
     fn split_command(command: &str) -> Vec<String> {
@@ -109,27 +232,209 @@ pub fn shlex_split(in_str: &str) -> Vec<String> {
     shl.by_ref().collect()
 }
 
-pub fn run_command(raw_command: Vec<String>, system_shell: &dyn Shell) -> CrabCommand {
-    let command = prepare_command(raw_command);
-    let mut output = shell_command(&system_shell.get_shell())
-        .arg(&command)
+/// Re-runs the failed command to capture its output.
+///
+/// The command is killed when it takes longer than the timeout from the settings
+/// (see `Settings::get_command_timeout`), keeping the output it printed so far.
+pub fn run_command(
+    raw_command: Vec<String>,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+) -> CrabCommand {
+    let command = prepare_command(raw_command, system_shell);
+    run_script(command, system_shell, settings)
+}
+
+/// Runs `script` in the shell, with the extra environment variables of the settings
+/// (by default, forcing the English messages the rules match).
+///
+/// The script runs in its own process group, so the whole group (e.g. the processes
+/// started by a shell script) is killed on timeout. Its stdin is closed, so a command
+/// waiting for input (like a password prompt) fails instead of hanging.
+fn run_script(script: String, system_shell: &dyn Shell, settings: &Settings) -> CrabCommand {
+    let timeout = settings.get_command_timeout(&shlex_split(&script));
+    let mut command = shell_command(&system_shell.get_shell());
+    command
+        .arg(&script)
+        .envs(settings.get_command_env())
+        .stdin(Stdio::null())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .expect("Command failed to start")
-        .wait_with_output()
-        .expect("failed to wait on child");
+        .stderr(Stdio::piped());
+    set_process_group(&mut command);
+    let mut child = command.spawn().expect("Command failed to start");
+
+    // The output is read as it comes, so it is still available if the command is killed
+    let (sender, receiver) = mpsc::channel();
+    let max_size = settings.max_output_size;
+    let captured = Arc::new(Mutex::new(CapturedOutput::default()));
+    let stdout = child.stdout.take();
+    read_in_background(stdout, Stream::Stdout, &captured, max_size, sender.clone());
+    let stderr = child.stderr.take();
+    read_in_background(stderr, Stream::Stderr, &captured, max_size, sender);
 
-    let exit_status = output.status;
-    // output.status.success();
-    let stdout = str::from_utf8(&output.stdout).map(|s| s.to_owned()).ok();
-    let stderr = str::from_utf8(&output.stderr).map(|s| s.to_owned()).ok();
-    CrabCommand::new(command, stdout, stderr)
+    if !wait_or_kill(&mut child, Instant::now() + timeout) {
+        logger::warn(&format!(
+            "Command timed out after {timeout:?}, using its partial output"
+        ));
+    }
+    let status = child.wait().ok();
+
+    // Background processes may keep the pipes open after the command exits
+    let output_deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+    for _ in 0..2 {
+        let remaining = output_deadline.saturating_duration_since(Instant::now());
+        if receiver.recv_timeout(remaining).is_err() {
+            break;
+        }
+    }
+
+    let output_chunks = captured.lock().unwrap().decode(max_size);
+    let join_stream = |stream| {
+        output_chunks
+            .iter()
+            .filter(|chunk| chunk.stream == stream)
+            .map(|chunk| chunk.text.as_str())
+            .collect::<String>()
+    };
+    let stdout = join_stream(Stream::Stdout);
+    let stderr = join_stream(Stream::Stderr);
+    CrabCommand::new(script, Some(stdout), Some(stderr))
+        .with_exit_status(
+            status.and_then(|status| status.code()),
+            status.and_then(get_signal),
+        )
+        .with_output_chunks(output_chunks)
 }
 
-fn prepare_command(raw_command: Vec<String>) -> String {
-    // TODO: Expand aliases (`shell.from_shell()`)
-    raw_command.join(" ").trim().to_owned()
+/// Output of a command, as the raw chunks read from its streams.
+#[derive(Debug, Default)]
+struct CapturedOutput {
+    chunks: VecDeque<(Stream, Vec<u8>)>,
+    size: usize,
+}
+
+impl CapturedOutput {
+    /// Appends `bytes` to the last chunk if it comes from the same stream. Only (at
+    /// least) the last `max_size` bytes are kept, as the error messages are usually at
+    /// the end.
+    fn push(&mut self, stream: Stream, bytes: &[u8], max_size: usize) {
+        match self.chunks.back_mut() {
+            Some((last_stream, last_bytes)) if *last_stream == stream => {
+                last_bytes.extend_from_slice(bytes)
+            }
+            _ => self.chunks.push_back((stream, bytes.to_vec())),
+        }
+        self.size += bytes.len();
+        // Dropping the beginning once in a while keeps the copies cheap
+        if self.size > 2 * max_size {
+            self.truncate_front(max_size);
+        }
+    }
+
+    fn truncate_front(&mut self, max_size: usize) {
+        while self.size > max_size {
+            let excess = self.size - max_size;
+            let Some((_, first_bytes)) = self.chunks.front_mut() else {
+                break;
+            };
+            if first_bytes.len() <= excess {
+                self.size -= first_bytes.len();
+                self.chunks.pop_front();
+            } else {
+                first_bytes.drain(..excess);
+                self.size -= excess;
+            }
+        }
+    }
+
+    /// Decodes the last `max_size` bytes, replacing the invalid UTF-8 sequences (e.g.
+    /// binary data or Latin-1 file names), and removes their escape sequences.
+    fn decode(&mut self, max_size: usize) -> Vec<OutputChunk> {
+        self.truncate_front(max_size);
+        self.chunks
+            .iter()
+            .map(|(stream, bytes)| OutputChunk {
+                stream: *stream,
+                text: strip_ansi_sequences(&String::from_utf8_lossy(bytes)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn get_signal(status: ExitStatus) -> Option<i32> {
+    status.signal()
+}
+
+#[cfg(not(target_family = "unix"))]
+fn get_signal(_status: ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Copies what is read from `pipe` into `captured`, from another thread. A message is
+/// sent on `done` when the pipe is closed.
+fn read_in_background<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    stream: Stream,
+    captured: &Arc<Mutex<CapturedOutput>>,
+    max_size: usize,
+    done: mpsc::Sender<()>,
+) {
+    let captured = captured.clone();
+    thread::spawn(move || {
+        if let Some(mut pipe) = pipe {
+            let mut chunk = [0; 4096];
+            while let Ok(size @ 1..) = pipe.read(&mut chunk) {
+                captured
+                    .lock()
+                    .unwrap()
+                    .push(stream, &chunk[..size], max_size);
+            }
+        }
+        let _ = done.send(());
+    });
+}
+
+/// Waits for `child` to exit until `deadline`, then kills its process group (see
+/// `set_process_group`). Returns `false` if it was killed; `child.wait()` must still
+/// be called to release it.
+pub fn wait_or_kill(child: &mut Child, deadline: Instant) -> bool {
+    loop {
+        match child.try_wait() {
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                kill_process_group(child);
+                return false;
+            }
+            Ok(Some(_)) | Err(_) => return true,
+        }
+    }
+}
+
+/// Runs `command` in its own process group, so `wait_or_kill` also kills the
+/// processes it starts (e.g. the commands of a shell script).
+pub fn set_process_group(command: &mut Command) {
+    #[cfg(target_family = "unix")]
+    command.process_group(0);
+}
+
+/// Kills `child` and the other processes of its group (see `set_process_group`).
+#[cfg(target_family = "unix")]
+pub fn kill_process_group(child: &mut Child) {
+    // The process group id is the pid of its leader, created with `process_group(0)`
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Joins the command parts and expands the shell alias it may start with.
+pub fn prepare_command(raw_command: Vec<String>, system_shell: &dyn Shell) -> String {
+    system_shell.expand_aliases(raw_command.join(" ").trim())
 }
 
 pub fn shell_command(words_str: &str) -> Command {
@@ -138,6 +443,8 @@ pub fn shell_command(words_str: &str) -> Command {
     let first_cmd = words.next().expect("absent shell binary");
     let dash_c = if words_str.contains("cmd.exe") {
         "/c"
+    } else if words_str.contains("pwsh") || words_str.contains("powershell") {
+        "-Command"
     } else {
         "-c"
     };
@@ -154,7 +461,53 @@ mod tests {
         shell::{Bash, Shell},
     };
 
-    use super::run_command;
+    use super::{
+        run_command, run_script, CapturedOutput, CorrectedCommand, CrabCommand, OutputChunk,
+        Stream, EXIT_CODE_NOT_FOUND,
+    };
+    use crate::config::Settings;
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    fn side_effect(old_command: CrabCommand, new_script: Option<&str>) {}
+
+    #[test]
+    fn test_corrected_command_serialize() {
+        let corrected_commands = vec![
+            CorrectedCommand::new(
+                "git branch".to_owned(),
+                None,
+                1000,
+                "git_not_command".to_owned(),
+            ),
+            CorrectedCommand::new(
+                "mkdir -p a".to_owned(),
+                Some(side_effect),
+                2000,
+                "mkdir_p".to_owned(),
+            ),
+        ];
+        assert_eq!(
+            serde_json::to_string(&corrected_commands).unwrap(),
+            "[{\"script\":\"git branch\",\"priority\":1000,\"rule\":\"git_not_command\",\"rules\":[\"git_not_command\"],\"side_effect\":false},\
+            {\"script\":\"mkdir -p a\",\"priority\":2000,\"rule\":\"mkdir_p\",\"rules\":[\"mkdir_p\"],\"side_effect\":true}]"
+        );
+    }
+
+    #[test]
+    fn test_with_edited_script() {
+        let command = CorrectedCommand::new(
+            "mkdir -p a".to_owned(),
+            Some(side_effect),
+            2000,
+            "mkdir_p".to_owned(),
+        );
+        let edited = command.with_edited_script("mkdir -p b".to_owned());
+        assert_eq!(edited.script, "mkdir -p b");
+        assert!(edited.side_effect.is_none());
+        assert_eq!(edited.priority, 2000);
+        assert_eq!(edited.rule_names, vec!["mkdir_p"]);
+    }
 
     #[test]
     fn test_shell_command() {
@@ -162,6 +515,12 @@ mod tests {
         let cmd = shell_command(&shell_name);
         assert_eq!(cmd.get_args().len(), 1);
         assert_eq!(cmd.get_program().to_str().unwrap(), shell_name);
+
+        let cmd = shell_command("pwsh -NoProfile");
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            vec!["-NoProfile", "-Command"]
+        );
     }
 
     #[cfg(target_family = "unix")]
@@ -177,8 +536,9 @@ mod tests {
         let command_vec = vec![terminal_command.to_owned(), "Hello!".to_owned()];
         let command = command_vec.join(" ").trim().to_owned();
         let system_shell: Box<dyn Shell> = Box::new(Bash {});
-        let crab_command = run_command(command_vec, &*system_shell);
+        let crab_command = run_command(command_vec, &*system_shell, &Settings::default());
         assert_eq!(crab_command.script, command);
+        assert!(crab_command.succeeded());
         assert_eq!(crab_command.output.unwrap(), "Hello!\n");
     }
 
@@ -188,11 +548,188 @@ mod tests {
         let command_vec = vec!["ls".to_owned(), "non_existent_directory".to_owned()];
         let command = command_vec.join(" ").trim().to_owned();
         let system_shell: Box<dyn Shell> = Box::new(Bash {});
-        let crab_command = run_command(command_vec, &*system_shell);
+        let crab_command = run_command(command_vec, &*system_shell, &Settings::default());
         assert_eq!(crab_command.script, command);
+        assert_eq!(crab_command.exit_code, Some(2));
+        assert_eq!(crab_command.signal, None);
         assert!(crab_command
             .output
             .unwrap()
             .contains("No such file or directory"));
     }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_timeout() {
+        let start = Instant::now();
+        let crab_command = run_script(
+            "echo partial; sleep 10; echo never".to_owned(),
+            &Bash {},
+            &Settings {
+                command_timeout: 300,
+                ..Settings::default()
+            },
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(crab_command.exit_code, None);
+        assert_eq!(crab_command.signal, Some(9));
+        assert_eq!(crab_command.output.unwrap(), "partial\n");
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_kills_process_group() {
+        // The background `sleep` keeps the pipes open unless the whole group is killed
+        let start = Instant::now();
+        let crab_command = run_script(
+            "sleep 10 & echo started; wait".to_owned(),
+            &Bash {},
+            &Settings {
+                command_timeout: 300,
+                ..Settings::default()
+            },
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(crab_command.output.unwrap(), "started\n");
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_background_process() {
+        // The output is not awaited until the timeout once the command has exited
+        let start = Instant::now();
+        let crab_command = run_script(
+            "sleep 3 & echo started".to_owned(),
+            &Bash {},
+            &Settings {
+                command_timeout: 10000,
+                ..Settings::default()
+            },
+        );
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(crab_command.exit_code, Some(0));
+        assert_eq!(crab_command.output.unwrap(), "started\n");
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_not_found() {
+        let crab_command = run_script(
+            "ohcrab_missing_command".to_owned(),
+            &Bash {},
+            &Settings::default(),
+        );
+        assert_eq!(crab_command.exit_code, Some(EXIT_CODE_NOT_FOUND));
+        assert!(!crab_command.succeeded());
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_env() {
+        let crab_command = run_script(
+            "echo $LC_ALL".to_owned(),
+            &Bash {},
+            &Settings {
+                command_env: HashMap::from([("LC_ALL".to_owned(), "C".to_owned())]),
+                ..Settings::default()
+            },
+        );
+        assert_eq!(crab_command.output.unwrap(), "C\n");
+    }
+
+    #[test]
+    fn test_update_keeps_exit_status() {
+        let command = CrabCommand::new("git brnch".to_owned(), None, None)
+            .with_exit_status(Some(1), None)
+            .update(Some("git branch".to_owned()), None, None);
+        assert_eq!(command.script, "git branch");
+        assert_eq!(command.exit_code, Some(1));
+    }
+
+    #[test]
+    fn test_captured_output() {
+        let mut captured = CapturedOutput::default();
+        captured.push(Stream::Stdout, b"caf\xe9 ", 100);
+        captured.push(Stream::Stdout, b"ok\n", 100);
+        captured.push(Stream::Stderr, b"\x1b[31merror\x1b[0m: oops\n", 100);
+        captured.push(Stream::Stdout, b"done\n", 100);
+        assert_eq!(
+            captured.decode(100),
+            vec![
+                OutputChunk {
+                    stream: Stream::Stdout,
+                    text: "caf\u{fffd} ok\n".to_owned()
+                },
+                OutputChunk {
+                    stream: Stream::Stderr,
+                    text: "error: oops\n".to_owned()
+                },
+                OutputChunk {
+                    stream: Stream::Stdout,
+                    text: "done\n".to_owned()
+                },
+            ]
+        );
+
+        // Only the end is kept
+        assert_eq!(
+            captured.decode(8),
+            vec![
+                OutputChunk {
+                    stream: Stream::Stderr,
+                    text: "ps\n".to_owned()
+                },
+                OutputChunk {
+                    stream: Stream::Stdout,
+                    text: "done\n".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_streams() {
+        let crab_command = run_script(
+            "echo out; echo err >&2; echo out again".to_owned(),
+            &Bash {},
+            &Settings::default(),
+        );
+        assert_eq!(crab_command.stdout.as_deref(), Some("out\nout again\n"));
+        assert_eq!(crab_command.stderr.as_deref(), Some("err\n"));
+        assert_eq!(
+            crab_command.output.as_deref(),
+            Some("out\nout again\n\nerr\n")
+        );
+        // How the chunks of the two streams interleave depends on the scheduling, but
+        // each stream keeps its order
+        for (stream, text) in [
+            (Stream::Stdout, "out\nout again\n"),
+            (Stream::Stderr, "err\n"),
+        ] {
+            let stream_text: String = crab_command
+                .output_chunks
+                .iter()
+                .filter(|chunk| chunk.stream == stream)
+                .map(|chunk| chunk.text.as_str())
+                .collect();
+            assert_eq!(stream_text, text);
+        }
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_run_script_max_output_size() {
+        let crab_command = run_script(
+            "seq 1 100000; echo 'the end'".to_owned(),
+            &Bash {},
+            &Settings {
+                max_output_size: 1000,
+                ..Settings::default()
+            },
+        );
+        let output = crab_command.output.unwrap();
+        assert_eq!(output.len(), 1000);
+        assert!(output.ends_with("99999\n100000\nthe end\n"));
+    }
 }
@@ -0,0 +1,141 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::{env, fs};
+
+use crate::utils::strip_ansi_sequences;
+use crate::ENV_VAR_NAME_OUTPUT_LOG;
+
+/// Sequence printed by the shell before each prompt in instant mode. It is an
+/// operating system command unknown to the terminals, so it is never displayed.
+pub const OUTPUT_MARK: &str = "\x1b]7770;ohcrab\x07";
+/// Same as `OUTPUT_MARK`, written for `printf` in the shell functions.
+pub const OUTPUT_MARK_PRINTF: &str = r"\033]7770;ohcrab\007";
+/// Only the end of the session log is read, it holds the last commands.
+const LOG_TAIL_SIZE: u64 = 1024 * 1024;
+
+/// Returns the output of the last command from the session log written in instant
+/// mode, or `None` if instant mode is disabled or the log does not end with
+/// `command_script`. Like the output of a command run again, only its last
+/// `max_size` bytes are kept.
+pub fn read_last_output(command_script: &str, max_size: usize) -> Option<String> {
+    let log_file = env::var_os(ENV_VAR_NAME_OUTPUT_LOG)?;
+    let log = read_log_tail(Path::new(&log_file)).ok()?;
+    let output = parse_last_output(&log, command_script)?;
+    Some(keep_end(&output, max_size).to_owned())
+}
+
+/// Returns the last `max_size` bytes of `text`, or a bit less so it does not start
+/// in the middle of a character.
+fn keep_end(text: &str, max_size: usize) -> &str {
+    let mut start = text.len().saturating_sub(max_size);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+fn read_log_tail(log_file: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(log_file)?;
+    let size = file.metadata()?.len();
+    file.seek(SeekFrom::Start(size.saturating_sub(LOG_TAIL_SIZE)))?;
+    let mut content = vec![];
+    file.read_to_end(&mut content)?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// Extracts the output of `command_script` from the session log.
+///
+/// The log ends with the call to ohcrab, preceded by the mark printed before its
+/// prompt. The previous mark starts the line holding the prompt of the failed command
+/// and the command itself (as echoed by the terminal), followed by its output.
+///
+/// The prompt must fit on that line: the lines after it are output, and may end
+/// with the script too (e.g. a command printing it).
+fn parse_last_output(log: &str, command_script: &str) -> Option<String> {
+    let mut parts = log.rsplit(OUTPUT_MARK);
+    let last_command = parts.nth(1)?;
+    // Without a mark before the failed command, the log may start in the middle of it
+    parts.next()?;
+
+    let lines: Vec<String> = last_command.split('\n').map(clean_line).collect();
+    if !lines[0].trim_end().ends_with(command_script.trim()) {
+        return None;
+    }
+    Some(lines[1..].join("\n"))
+}
+
+/// Removes the escape sequences (colors, cursor moves) and carriage returns the
+/// terminal recorded in the log.
+fn clean_line(line: &str) -> String {
+    strip_ansi_sequences(line).replace('\r', "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clean_line, keep_end, parse_last_output, OUTPUT_MARK};
+
+    #[test]
+    fn test_clean_line() {
+        assert_eq!(
+            clean_line("\x1b[01;32muser@host\x1b[00m:~$ git brnch\r"),
+            "user@host:~$ git brnch"
+        );
+        assert_eq!(clean_line("\x1b]0;title\x07\x1b[?2004hls"), "ls");
+    }
+
+    #[test]
+    fn test_keep_end() {
+        assert_eq!(keep_end("error: oops\n", 100), "error: oops\n");
+        assert_eq!(keep_end("error: oops\n", 5), "oops\n");
+        // `é` takes two bytes
+        assert_eq!(keep_end("café\n", 2), "\n");
+        assert_eq!(keep_end("café\n", 3), "é\n");
+    }
+
+    #[test]
+    fn test_parse_last_output() {
+        let log = format!(
+            "Script started\r\n{OUTPUT_MARK}$ ls\r\nfile\r\n\
+            {OUTPUT_MARK}\x1b[?2004h$ git brnch\r\n\x1b[?2004l\r\
+            git: 'brnch' is not a git command. See 'git --help'.\r\n\r\n\
+            The most similar command is\r\n\tbranch\r\n\
+            {OUTPUT_MARK}$ crab\r\n"
+        );
+        assert_eq!(
+            parse_last_output(&log, "git brnch"),
+            Some(
+                "git: 'brnch' is not a git command. See 'git --help'.\n\n\
+                The most similar command is\n\tbranch\n"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_last_output_command_line() {
+        // Only the line of the prompt holds the command
+        let log = format!("{OUTPUT_MARK}$ ls\r\ncd foo\r\n{OUTPUT_MARK}$ crab");
+        assert_eq!(parse_last_output(&log, "cd foo"), None);
+        let log = format!("{OUTPUT_MARK}$ echo cd foo\r\ncd foo\r\n{OUTPUT_MARK}$ crab");
+        assert_eq!(
+            parse_last_output(&log, "echo cd foo"),
+            Some("cd foo\n".to_owned())
+        );
+        // Multi-line prompts are not supported, the command is run again instead
+        let log = format!("{OUTPUT_MARK}~/src\r\n$ cd foo\r\nno such dir\r\n{OUTPUT_MARK}$ crab");
+        assert_eq!(parse_last_output(&log, "cd foo"), None);
+    }
+
+    #[test]
+    fn test_parse_last_output_mismatch() {
+        let log = format!("{OUTPUT_MARK}$ ls\r\nfile\r\n{OUTPUT_MARK}$ crab\r\n");
+        // The log does not hold the command being corrected
+        assert_eq!(parse_last_output(&log, "git brnch"), None);
+        // Not enough marks to find the command
+        assert_eq!(parse_last_output("$ ls\r\nfile\r\n", "ls"), None);
+        assert_eq!(
+            parse_last_output(&format!("$ ls\r\nfile\r\n{OUTPUT_MARK}$ crab"), "ls"),
+            None
+        );
+    }
+}
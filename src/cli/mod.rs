@@ -1,2 +1,3 @@
 pub mod command;
+pub mod instant_mode;
 pub mod parser;
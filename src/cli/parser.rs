@@ -1,13 +1,16 @@
 // FILE: ./src/cli/parser.rs
-use clap::{command, Arg, ArgAction};
+use clap::{command, Arg, ArgAction, Command};
 use std::env;
 
 use crate::{ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_SHELL};
 
+pub const RULES_SUBCOMMAND: &str = "rules";
+
 /// Prepares arguments by:
 /// - Removing placeholder and moving arguments after it to beginning, we need this
 ///   to distinguish arguments from `command` with ours;
-/// - Adding `--` before `command`, so that our parser ignores arguments of `command`.
+/// - Adding `--` before `command`, so that our parser ignores arguments of `command`
+///   (unless it is the `rules` subcommand).
 ///
 /// * `argv`:
 pub fn prepare_arguments(mut argv: Vec<String>) -> Vec<String> {
@@ -21,7 +24,11 @@ pub fn prepare_arguments(mut argv: Vec<String>) -> Vec<String> {
         processed_args.append(&mut argv);
         processed_args
     } else {
-        if !argv.is_empty() && !argv[0].starts_with('-') && argv[0] != "--" {
+        if !argv.is_empty()
+            && !argv[0].starts_with('-')
+            && argv[0] != "--"
+            && argv[0] != RULES_SUBCOMMAND
+        {
             argv.insert(0, "--".to_owned());
         }
         argv
@@ -41,6 +48,13 @@ pub fn get_parser() -> clap::Command {
                 .env(ENV_VAR_NAME_ALIAS)
                 .default_value("crab"),
         )
+        .arg(
+            Arg::new("enable-instant-mode")
+                .long("enable-instant-mode")
+                .help("With the shell function, log the shell session so the output of the failed command is read instead of running it again (bash and zsh only)")
+                .action(ArgAction::SetTrue)
+                .required(false),
+        )
         .arg(
             Arg::new("shell")
                 .long("shell")
@@ -54,7 +68,7 @@ pub fn get_parser() -> clap::Command {
             Arg::new("debug")
                 .long("debug")
                 .short('d')
-                .help("Enable debug output")
+                .help("Enable debug output, on stderr or in the file set in OHCRAB_LOG_FILE. Also enabled by OHCRAB_DEBUG")
                 .action(ArgAction::SetTrue)
                 .required(false),
         )
@@ -66,6 +80,21 @@ pub fn get_parser() -> clap::Command {
                 .action(ArgAction::SetTrue)
                 .required(false),
         )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .help("Show the verdict of every rule for the command instead of correcting it")
+                .action(ArgAction::SetTrue)
+                .required(false),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format of the corrected commands. With `json`, all the candidates are printed and no side effect is run")
+                .value_parser(["text", "json"])
+                .required(false)
+                .default_value("text"),
+        )
         .arg(
             Arg::new("command")
                 .help("Command that should be fixed")
@@ -74,6 +103,33 @@ pub fn get_parser() -> clap::Command {
                 .env(ENV_VAR_NAME_HISTORY)
                 .last(true),
         )
+        .subcommand(
+            Command::new(RULES_SUBCOMMAND)
+                .about("Lists the rules, their priority and whether they are enabled")
+                .subcommand(
+                    Command::new("explain")
+                        .about("Runs a single rule against a command and explains the result")
+                        .arg(
+                            Arg::new("name")
+                                .help("Name of the rule")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("output")
+                                .long("output")
+                                .short('o')
+                                .help("Output of the command, stdout and stderr together. If absent, the command is run to get it")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::new("command")
+                                .help("Command the rule is run against")
+                                .action(ArgAction::Append)
+                                .required(true)
+                                .last(true),
+                        ),
+                ),
+        )
 }
 
 #[cfg(test)]
@@ -159,6 +215,36 @@ mod tests {
             Some(&"pws".to_string())
         );
 
+        // Test format
+        assert_eq!(
+            get_parser()
+                .get_matches_from(Vec::<String>::new())
+                .get_one::<String>("format"),
+            Some(&"text".to_string())
+        );
+        assert_eq!(
+            get_parser()
+                .get_matches_from(vec!["--format", "json"])
+                .get_one::<String>("format"),
+            Some(&"json".to_string())
+        );
+        assert!(get_parser()
+            .try_get_matches_from(vec!["--format", "yaml"])
+            .is_err());
+
+        // Test explain flag
+        assert!(!get_parser()
+            .get_matches_from(vec!["--", "anything"])
+            .get_flag("explain"));
+        assert!(get_parser()
+            .get_matches_from(vec!["--explain", "--", "anything"])
+            .get_flag("explain"));
+
+        // Test instant mode flag
+        assert!(get_parser()
+            .get_matches_from(vec!["--alias", "crab", "--enable-instant-mode"])
+            .get_flag("enable-instant-mode"));
+
         // Test debug flag
         assert!(get_parser()
             .get_matches_from(vec!["-d", "--", "anything"])
@@ -210,11 +296,56 @@ mod tests {
                 vec!["-param".to_owned(), "arg2".to_owned(), "arg3".to_owned()],
                 vec!["-param", "arg2", "arg3"],
             ),
+            (
+                vec!["rules".to_owned(), "explain".to_owned(), "sudo".to_owned()],
+                vec!["rules", "explain", "sudo"],
+            ),
         ] {
             assert_eq!(prepare_arguments(input), exp_output);
         }
     }
 
+    #[test]
+    fn test_parser_rules_subcommand() {
+        let matches = get_parser().get_matches_from(vec!["rules"]);
+        let (name, rules_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, "rules");
+        assert!(rules_matches.subcommand().is_none());
+
+        let matches = get_parser().get_matches_from(vec![
+            "rules",
+            "explain",
+            "sudo",
+            "--output",
+            "Permission denied",
+            "--",
+            "ls",
+            "/root",
+        ]);
+        let (_, rules_matches) = matches.subcommand().unwrap();
+        let (name, explain_matches) = rules_matches.subcommand().unwrap();
+        assert_eq!(name, "explain");
+        assert_eq!(
+            explain_matches.get_one::<String>("name"),
+            Some(&"sudo".to_owned())
+        );
+        assert_eq!(
+            explain_matches.get_one::<String>("output"),
+            Some(&"Permission denied".to_owned())
+        );
+        assert_eq!(
+            explain_matches
+                .get_many::<String>("command")
+                .unwrap()
+                .collect::<Vec<_>>(),
+            ["ls", "/root"]
+        );
+
+        assert!(get_parser()
+            .try_get_matches_from(vec!["rules", "explain", "sudo"])
+            .is_err());
+    }
+
     /// Tests the argument processing logic.
     ///
     /// This test checks if the argument processing functions work as expected. It prepares some
@@ -0,0 +1,454 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::{env, fs};
+
+use crate::logger;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+/// Locale of the commands run again by default.
+const UTF8_LOCALE: &str = "C.UTF-8";
+/// Replaces `UTF8_LOCALE` on the systems lacking it.
+const FALLBACK_LOCALE: &str = "C";
+const ENV_VAR_CONFIG_DIR: &str = "OHCRAB_CONFIG_DIR";
+const ENV_VAR_CACHE_DIR: &str = "OHCRAB_CACHE_DIR";
+const ENV_VAR_ENABLED_RULES: &str = "OHCRAB_ENABLED_RULES";
+const ENV_VAR_DISABLED_RULES: &str = "OHCRAB_DISABLED_RULES";
+const ENV_VAR_PRIORITY: &str = "OHCRAB_PRIORITY";
+const ENV_VAR_NUM_CLOSE_MATCHES: &str = "OHCRAB_NUM_CLOSE_MATCHES";
+const ENV_VAR_CLOSE_MATCH_CUTOFF: &str = "OHCRAB_CLOSE_MATCH_CUTOFF";
+const ENV_VAR_HISTORY_LIMIT: &str = "OHCRAB_HISTORY_LIMIT";
+const ENV_VAR_PLUGIN_TIMEOUT: &str = "OHCRAB_PLUGIN_TIMEOUT";
+const ENV_VAR_RULE_BUDGET: &str = "OHCRAB_RULE_BUDGET";
+const ENV_VAR_COMMAND_TIMEOUT: &str = "OHCRAB_COMMAND_TIMEOUT";
+const ENV_VAR_SLOW_COMMANDS: &str = "OHCRAB_SLOW_COMMANDS";
+const ENV_VAR_SLOW_COMMAND_TIMEOUT: &str = "OHCRAB_SLOW_COMMAND_TIMEOUT";
+const ENV_VAR_COMMAND_ENV: &str = "OHCRAB_COMMAND_ENV";
+const ENV_VAR_MAX_OUTPUT_SIZE: &str = "OHCRAB_MAX_OUTPUT_SIZE";
+
+/// User settings, read from `config.toml` and overridden by `OHCRAB_*` environment
+/// variables.
+///
+/// Example of a configuration file:
+///
+/// ```toml
+/// # Rules that are disabled by default but should run anyway
+/// enabled_rules = ["git_push_force"]
+/// # Rules that should never run
+/// disabled_rules = ["history", "no_command"]
+/// num_close_matches = 5
+/// close_match_cutoff = 0.7
+/// history_limit = 2000
+/// # Maximum time (in milliseconds) a rule plugin can take to answer
+/// plugin_timeout = 500
+/// # Time (in milliseconds) after which the rules still running are ignored
+/// rule_budget = 2000
+/// # Time (in milliseconds) after which the re-run of the failed command is killed
+/// command_timeout = 3000
+/// # Commands that are given `slow_command_timeout` instead
+/// slow_commands = ["gradle", "vagrant"]
+/// slow_command_timeout = 15000
+/// # Number of bytes kept from the end of the output (stdout and stderr) of the command
+/// max_output_size = 65536
+///
+/// [priority]
+/// git_push = 100
+///
+/// # Environment variables set when the failed command is run again
+/// [command_env]
+/// LC_ALL = "C.UTF-8"
+/// LANGUAGE = "en"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub enabled_rules: Vec<String>,
+    pub disabled_rules: Vec<String>,
+    pub priority: HashMap<String, u16>,
+    pub num_close_matches: usize,
+    pub close_match_cutoff: f64,
+    pub history_limit: Option<usize>,
+    pub plugin_timeout: u64,
+    pub rule_budget: u64,
+    pub command_timeout: u64,
+    pub slow_commands: Vec<String>,
+    pub slow_command_timeout: u64,
+    pub command_env: HashMap<String, String>,
+    pub max_output_size: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            enabled_rules: vec![],
+            disabled_rules: vec![],
+            priority: HashMap::new(),
+            num_close_matches: 3,
+            close_match_cutoff: 0.6,
+            history_limit: None,
+            plugin_timeout: 1000,
+            rule_budget: 3000,
+            command_timeout: 3000,
+            slow_commands: vec![
+                "lein".to_owned(),
+                "react-native".to_owned(),
+                "gradle".to_owned(),
+                "./gradlew".to_owned(),
+                "vagrant".to_owned(),
+            ],
+            slow_command_timeout: 15000,
+            // The rules match English messages, and the file names keep their accents
+            command_env: HashMap::from([
+                ("LC_ALL".to_owned(), UTF8_LOCALE.to_owned()),
+                ("LANGUAGE".to_owned(), "en".to_owned()),
+            ]),
+            max_output_size: 256 * 1024,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings from the configuration file (if it exists) and applies the
+    /// overrides from the environment variables.
+    ///
+    /// Errors in the configuration file are logged as warnings and the default values
+    /// are used instead, so a broken file never prevents the correction. An invalid
+    /// key only loses its own value, the other keys still apply.
+    pub fn load() -> Self {
+        let config_file = get_config_dir().join(CONFIG_FILE_NAME);
+        let mut settings = match fs::read_to_string(&config_file) {
+            Ok(content) => match Settings::from_toml(&content) {
+                Ok((settings, errors)) => {
+                    for error in errors {
+                        logger::warn(&format!(
+                            "Ignoring invalid key of {}: {error}",
+                            config_file.display()
+                        ));
+                    }
+                    settings
+                }
+                Err(err) => {
+                    logger::warn(&format!(
+                        "Ignoring invalid {}: {err}",
+                        config_file.display()
+                    ));
+                    Settings::default()
+                }
+            },
+            Err(_) => Settings::default(),
+        };
+        settings.apply_env_overrides(|name| env::var(name).ok());
+        settings
+    }
+
+    /// Parses the settings from the content of a TOML file.
+    ///
+    /// The keys that are unknown (e.g. misspelled) or have a value of the wrong type
+    /// are skipped, and their errors returned with the settings. Only a file that is
+    /// not valid TOML is an error.
+    pub fn from_toml(content: &str) -> Result<(Self, Vec<String>), toml::de::Error> {
+        let table: toml::Table = toml::from_str(content)?;
+        let mut valid_table = toml::Table::new();
+        let mut errors = vec![];
+        for (key, value) in table {
+            // Each key is checked alone, the others taking their default value
+            let key_table = toml::Table::from_iter([(key.clone(), value.clone())]);
+            match toml::Value::Table(key_table).try_into::<Settings>() {
+                Ok(_) => {
+                    valid_table.insert(key, value);
+                }
+                Err(err) => errors.push(format!("`{key}`: {}", err.message())),
+            }
+        }
+        Ok((toml::Value::Table(valid_table).try_into()?, errors))
+    }
+
+    /// Overrides the settings with the values returned by `get_env`.
+    ///
+    /// * `get_env`: Function returning the value of an environment variable, if set.
+    pub fn apply_env_overrides<F>(&mut self, get_env: F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(rules) = get_env(ENV_VAR_ENABLED_RULES) {
+            self.enabled_rules = split_list(&rules);
+        }
+        if let Some(rules) = get_env(ENV_VAR_DISABLED_RULES) {
+            self.disabled_rules = split_list(&rules);
+        }
+        if let Some(priorities) = get_env(ENV_VAR_PRIORITY) {
+            // Format: `rule_name=priority,other_rule=priority`
+            for item in split_list(&priorities) {
+                if let Some((name, value)) = item.split_once('=') {
+                    if let Ok(value) = value.trim().parse() {
+                        self.priority.insert(name.trim().to_owned(), value);
+                    }
+                }
+            }
+        }
+        if let Some(value) = get_env(ENV_VAR_NUM_CLOSE_MATCHES).and_then(|v| v.parse().ok()) {
+            self.num_close_matches = value;
+        }
+        if let Some(value) = get_env(ENV_VAR_CLOSE_MATCH_CUTOFF).and_then(|v| v.parse().ok()) {
+            self.close_match_cutoff = value;
+        }
+        if let Some(value) = get_env(ENV_VAR_HISTORY_LIMIT).and_then(|v| v.parse().ok()) {
+            self.history_limit = Some(value);
+        }
+        if let Some(value) = get_env(ENV_VAR_PLUGIN_TIMEOUT).and_then(|v| v.parse().ok()) {
+            self.plugin_timeout = value;
+        }
+        if let Some(value) = get_env(ENV_VAR_RULE_BUDGET).and_then(|v| v.parse().ok()) {
+            self.rule_budget = value;
+        }
+        if let Some(value) = get_env(ENV_VAR_COMMAND_TIMEOUT).and_then(|v| v.parse().ok()) {
+            self.command_timeout = value;
+        }
+        if let Some(commands) = get_env(ENV_VAR_SLOW_COMMANDS) {
+            self.slow_commands = split_list(&commands);
+        }
+        if let Some(value) = get_env(ENV_VAR_SLOW_COMMAND_TIMEOUT).and_then(|v| v.parse().ok()) {
+            self.slow_command_timeout = value;
+        }
+        if let Some(variables) = get_env(ENV_VAR_COMMAND_ENV) {
+            // Format: `NAME=value,OTHER_NAME=value`, empty to set no variable
+            self.command_env = split_list(&variables)
+                .iter()
+                .filter_map(|item| item.split_once('='))
+                .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+                .collect();
+        }
+        if let Some(value) = get_env(ENV_VAR_MAX_OUTPUT_SIZE).and_then(|v| v.parse().ok()) {
+            self.max_output_size = value;
+        }
+    }
+
+    /// Returns how long the re-run of `script_parts` may take before it is killed.
+    pub fn get_command_timeout(&self, script_parts: &[String]) -> Duration {
+        let is_slow = script_parts
+            .first()
+            .is_some_and(|binary| self.slow_commands.contains(binary));
+        if is_slow {
+            Duration::from_millis(self.slow_command_timeout)
+        } else {
+            Duration::from_millis(self.command_timeout)
+        }
+    }
+
+    /// Returns the environment variables of the commands run again: `command_env`,
+    /// where `C.UTF-8` is replaced by `C` if the system does not have it.
+    pub fn get_command_env(&self) -> HashMap<String, String> {
+        let mut command_env = self.command_env.clone();
+        for value in command_env.values_mut() {
+            if value == UTF8_LOCALE && !is_utf8_locale_available() {
+                *value = FALLBACK_LOCALE.to_owned();
+            }
+        }
+        command_env
+    }
+}
+
+/// Returns `true` if the C library knows `UTF8_LOCALE` (older glibc versions and
+/// macOS may not). The check is done once per process.
+fn is_utf8_locale_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    // A locale that does not exist leaves the current one unchanged. `OnceLock` also
+    // makes sure only one thread calls `setlocale`.
+    *AVAILABLE.get_or_init(|| set_locale(UTF8_LOCALE) && set_locale(FALLBACK_LOCALE))
+}
+
+/// Changes the locale of the process, returning `false` if it does not exist. Rust
+/// programs never use it, so it is only changed to be checked, then restored to `C`.
+#[cfg(target_family = "unix")]
+fn set_locale(locale: &str) -> bool {
+    let locale = std::ffi::CString::new(locale).unwrap();
+    unsafe { !libc::setlocale(libc::LC_ALL, locale.as_ptr()).is_null() }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn set_locale(_locale: &str) -> bool {
+    true
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_owned())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Returns the directory holding the ohcrab configuration.
+///
+/// It is `$OHCRAB_CONFIG_DIR` if set, otherwise `$XDG_CONFIG_HOME/ohcrab`, falling
+/// back to `~/.config/ohcrab`.
+pub fn get_config_dir() -> PathBuf {
+    if let Ok(dir) = env::var(ENV_VAR_CONFIG_DIR) {
+        return PathBuf::from(dir);
+    }
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => dirs::home_dir().unwrap_or_default().join(".config"),
+    }
+    .join("ohcrab")
+}
+
+/// Returns the directory holding the files cached by ohcrab.
+///
+/// It is `$OHCRAB_CACHE_DIR` if set, otherwise `$XDG_CACHE_HOME/ohcrab`, falling
+/// back to `~/.cache/ohcrab`.
+pub fn get_cache_dir() -> PathBuf {
+    if let Ok(dir) = env::var(ENV_VAR_CACHE_DIR) {
+        return PathBuf::from(dir);
+    }
+    match env::var("XDG_CACHE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => dirs::home_dir().unwrap_or_default().join(".cache"),
+    }
+    .join("ohcrab")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_utf8_locale_available, Settings};
+    use rstest::rstest;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_toml() {
+        let (settings, errors) = Settings::from_toml(
+            r#"
+            disabled_rules = ["history", "no_command"]
+            enabled_rules = ["git_push_force"]
+            num_close_matches = 5
+            history_limit = 100
+
+            [priority]
+            git_push = 100
+
+            [command_env]
+            LC_ALL = "de_DE.UTF-8"
+            "#,
+        )
+        .unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(settings.disabled_rules, vec!["history", "no_command"]);
+        assert_eq!(settings.enabled_rules, vec!["git_push_force"]);
+        assert_eq!(settings.num_close_matches, 5);
+        assert_eq!(settings.close_match_cutoff, 0.6);
+        assert_eq!(settings.history_limit, Some(100));
+        assert_eq!(settings.priority.get("git_push"), Some(&100));
+        assert_eq!(
+            settings.command_env,
+            HashMap::from([("LC_ALL".to_owned(), "de_DE.UTF-8".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_from_toml_empty() {
+        assert_eq!(
+            Settings::from_toml("").unwrap(),
+            (Settings::default(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_from_toml_invalid_keys() {
+        // The valid keys still apply
+        let (settings, errors) = Settings::from_toml(
+            r#"
+            disabled_rules = ["history"]
+            disabled_rule = ["sudo"]
+            num_close_matches = "three"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(settings.disabled_rules, vec!["history"]);
+        assert_eq!(settings.num_close_matches, 3);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("`disabled_rule`: unknown field"));
+        assert!(errors[1].starts_with("`num_close_matches`: invalid type"));
+    }
+
+    #[test]
+    fn test_from_toml_invalid() {
+        assert!(Settings::from_toml("disabled_rules = [").is_err());
+    }
+
+    #[test]
+    fn test_apply_env_overrides() {
+        let env = HashMap::from([
+            ("OHCRAB_DISABLED_RULES", "history, no_command"),
+            ("OHCRAB_PRIORITY", "git_push=100,sudo=50,broken"),
+            ("OHCRAB_NUM_CLOSE_MATCHES", "7"),
+            ("OHCRAB_CLOSE_MATCH_CUTOFF", "0.8"),
+            ("OHCRAB_HISTORY_LIMIT", "not a number"),
+            ("OHCRAB_PLUGIN_TIMEOUT", "250"),
+            ("OHCRAB_RULE_BUDGET", "1500"),
+            ("OHCRAB_COMMAND_TIMEOUT", "2000"),
+            ("OHCRAB_SLOW_COMMANDS", "npm, cargo"),
+            ("OHCRAB_COMMAND_ENV", "LANG=C.UTF-8, broken"),
+            ("OHCRAB_MAX_OUTPUT_SIZE", "4096"),
+        ]);
+        let (mut settings, _) = Settings::from_toml("disabled_rules = [\"sudo\"]").unwrap();
+        settings.apply_env_overrides(|name| env.get(name).map(|v| v.to_string()));
+
+        assert_eq!(settings.disabled_rules, vec!["history", "no_command"]);
+        assert!(settings.enabled_rules.is_empty());
+        assert_eq!(
+            settings.priority,
+            HashMap::from([("git_push".to_owned(), 100), ("sudo".to_owned(), 50)])
+        );
+        assert_eq!(settings.num_close_matches, 7);
+        assert_eq!(settings.close_match_cutoff, 0.8);
+        assert_eq!(settings.history_limit, None);
+        assert_eq!(settings.plugin_timeout, 250);
+        assert_eq!(settings.rule_budget, 1500);
+        assert_eq!(settings.command_timeout, 2000);
+        assert_eq!(settings.slow_commands, vec!["npm", "cargo"]);
+        assert_eq!(settings.slow_command_timeout, 15000);
+        assert_eq!(
+            settings.command_env,
+            HashMap::from([("LANG".to_owned(), "C.UTF-8".to_owned())])
+        );
+        assert_eq!(settings.max_output_size, 4096);
+    }
+
+    #[rstest]
+    #[case(&["git", "push"], 3000)]
+    #[case(&["gradle", "build"], 15000)]
+    #[case(&["./gradlew"], 15000)]
+    #[case(&[], 3000)]
+    fn test_get_command_timeout(#[case] script_parts: &[&str], #[case] expected: u64) {
+        let script_parts: Vec<String> = script_parts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            Settings::default().get_command_timeout(&script_parts),
+            Duration::from_millis(expected)
+        );
+    }
+
+    #[test]
+    fn test_get_command_env() {
+        let expected_locale = if is_utf8_locale_available() {
+            "C.UTF-8"
+        } else {
+            "C"
+        };
+        assert_eq!(
+            Settings::default().get_command_env(),
+            HashMap::from([
+                ("LC_ALL".to_owned(), expected_locale.to_owned()),
+                ("LANGUAGE".to_owned(), "en".to_owned()),
+            ])
+        );
+
+        let settings = Settings {
+            command_env: HashMap::from([("LC_ALL".to_owned(), "de_DE.UTF-8".to_owned())]),
+            ..Settings::default()
+        };
+        assert_eq!(settings.get_command_env(), settings.command_env);
+    }
+}
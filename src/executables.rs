@@ -0,0 +1,226 @@
+use is_executable::IsExecutable;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs};
+
+use crate::config::get_cache_dir;
+use crate::logger;
+
+const INDEX_FILE_NAME: &str = "executables.json";
+/// Age after which the index is rebuilt anyway, to notice the files made executable
+/// (`chmod +x` does not change the modification time of their directory).
+const INDEX_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+static PATH_EXECUTABLES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Names of the executables found in the PATH, cached on disk.
+///
+/// The index is valid as long as the PATH and the modification time of each of its
+/// directories (which changes when a file is added or removed) are the same, and it
+/// is not older than `INDEX_MAX_AGE`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ExecutableIndex {
+    path: String,
+    /// When the index was built, in seconds since the Unix epoch.
+    built_at: u64,
+    mtimes: Vec<Option<(u64, u32)>>,
+    executables: Vec<String>,
+}
+
+/// Returns the names of the executables in the PATH, without duplicates.
+///
+/// The names are read from the index cached in `~/.cache/ohcrab`, which is rebuilt
+/// when the PATH or one of its directories changes, and every `INDEX_MAX_AGE`. They
+/// are computed only once per process.
+pub fn get_path_executables() -> &'static [String] {
+    PATH_EXECUTABLES.get_or_init(|| {
+        let path_var = env::var("PATH").unwrap_or_default();
+        logger::time(
+            || "PATH executables".to_owned(),
+            || load_executables(&get_cache_dir().join(INDEX_FILE_NAME), &path_var),
+        )
+    })
+}
+
+/// Returns the executables of `path_var` from the index in `index_file`, scanning the
+/// directories (and updating the index) if it is missing or outdated.
+fn load_executables(index_file: &Path, path_var: &str) -> Vec<String> {
+    let dirs: Vec<PathBuf> = env::split_paths(path_var).collect();
+    let mtimes = get_mtimes(&dirs);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+
+    let cached_index = fs::read_to_string(index_file)
+        .ok()
+        .and_then(|content| serde_json::from_str::<ExecutableIndex>(&content).ok());
+    if let Some(index) = cached_index {
+        let age = now.saturating_sub(index.built_at);
+        if index.path == path_var && index.mtimes == mtimes && age < INDEX_MAX_AGE.as_secs() {
+            return index.executables;
+        }
+    }
+
+    logger::debug("Executable index missing or outdated, scanning the PATH");
+    let index = ExecutableIndex {
+        path: path_var.to_owned(),
+        built_at: now,
+        mtimes,
+        executables: scan_executables(&dirs),
+    };
+    if let Err(err) = save_index(index_file, &index) {
+        logger::warn(&format!(
+            "Cannot write the executable index {}: {err}",
+            index_file.display()
+        ));
+    }
+    index.executables
+}
+
+fn get_mtimes(dirs: &[PathBuf]) -> Vec<Option<(u64, u32)>> {
+    dirs.iter()
+        .map(|dir| {
+            let mtime = fs::metadata(dir)
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            let since_epoch = mtime.duration_since(UNIX_EPOCH).ok()?;
+            Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+        })
+        .collect()
+}
+
+/// Lists the executable files of the directories, keeping the first occurrence of
+/// each name.
+fn scan_executables(dirs: &[PathBuf]) -> Vec<String> {
+    let mut names = HashSet::new();
+    let mut executables = vec![];
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() || !path.is_executable() {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                if names.insert(name.to_owned()) {
+                    executables.push(name.to_owned());
+                }
+            }
+        }
+    }
+    executables
+}
+
+/// Writes the index to a temporary file first, so concurrent runs never read a
+/// partially written index.
+fn save_index(index_file: &Path, index: &ExecutableIndex) -> std::io::Result<()> {
+    if let Some(parent) = index_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_file = index_file.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp_file, serde_json::to_string(index)?)?;
+    fs::rename(&tmp_file, index_file)
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::{load_executables, scan_executables, ExecutableIndex};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    fn write_file(dir: &Path, name: &str, mode: u32) {
+        let path = dir.join(name);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn test_scan_executables() {
+        let first_dir = tempfile::tempdir().unwrap();
+        let second_dir = tempfile::tempdir().unwrap();
+        write_file(first_dir.path(), "git", 0o755);
+        write_file(first_dir.path(), "notes.txt", 0o644);
+        fs::create_dir(first_dir.path().join("subdir")).unwrap();
+        write_file(second_dir.path(), "git", 0o755);
+        write_file(second_dir.path(), "ls", 0o700);
+
+        let mut executables = scan_executables(&[
+            first_dir.path().to_path_buf(),
+            second_dir.path().join("missing"),
+            second_dir.path().to_path_buf(),
+        ]);
+        executables.sort();
+        assert_eq!(executables, vec!["git", "ls"]);
+    }
+
+    #[test]
+    fn test_load_executables() {
+        let bin_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let index_file = cache_dir.path().join("ohcrab").join("executables.json");
+        let path_var = bin_dir.path().to_str().unwrap().to_owned();
+        write_file(bin_dir.path(), "git", 0o755);
+
+        // Built and saved on the first call
+        assert_eq!(load_executables(&index_file, &path_var), vec!["git"]);
+        let index: ExecutableIndex =
+            serde_json::from_str(&fs::read_to_string(&index_file).unwrap()).unwrap();
+        assert_eq!(index.path, path_var);
+        assert_eq!(index.executables, vec!["git"]);
+
+        // Read from the cache while the directory does not change
+        let cached_index = ExecutableIndex {
+            executables: vec!["cached".to_owned()],
+            ..index
+        };
+        fs::write(&index_file, serde_json::to_string(&cached_index).unwrap()).unwrap();
+        assert_eq!(load_executables(&index_file, &path_var), vec!["cached"]);
+
+        // Rebuilt when the directory was modified after the index was built
+        write_file(bin_dir.path(), "ls", 0o755);
+        let outdated_index = ExecutableIndex {
+            mtimes: vec![Some((0, 0))],
+            ..cached_index
+        };
+        fs::write(&index_file, serde_json::to_string(&outdated_index).unwrap()).unwrap();
+        let mut executables = load_executables(&index_file, &path_var);
+        executables.sort();
+        assert_eq!(executables, vec!["git", "ls"]);
+
+        // Rebuilt when the index is too old, even if the directory did not change
+        let old_index = ExecutableIndex {
+            built_at: 0,
+            executables: vec!["cached".to_owned()],
+            ..serde_json::from_str(&fs::read_to_string(&index_file).unwrap()).unwrap()
+        };
+        fs::write(&index_file, serde_json::to_string(&old_index).unwrap()).unwrap();
+        let mut executables = load_executables(&index_file, &path_var);
+        executables.sort();
+        assert_eq!(executables, vec!["git", "ls"]);
+
+        // Rebuilt when the PATH changes
+        let other_dir = tempfile::tempdir().unwrap();
+        let other_path_var = other_dir.path().to_str().unwrap();
+        assert!(load_executables(&index_file, other_path_var).is_empty());
+    }
+
+    #[test]
+    fn test_load_executables_invalid_index() {
+        let bin_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let index_file = cache_dir.path().join("executables.json");
+        fs::write(&index_file, "not json").unwrap();
+        write_file(bin_dir.path(), "git", 0o755);
+
+        assert_eq!(
+            load_executables(&index_file, bin_dir.path().to_str().unwrap()),
+            vec!["git"]
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+pub const ENV_VAR_DEBUG: &str = "OHCRAB_DEBUG";
+pub const ENV_VAR_LOG_FILE: &str = "OHCRAB_LOG_FILE";
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Level of a log message, from the most to the least important.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    /// Parses the value of `OHCRAB_DEBUG`: either the name of a level or a boolean,
+    /// `true` meaning the `debug` level. Returns `None` if logging is disabled.
+    pub fn from_env_value(value: &str) -> Option<Level> {
+        match value.trim().to_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "1" | "true" | "yes" | "on" | "debug" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        };
+        write!(f, "{name:5}")
+    }
+}
+
+/// Logger writing the messages up to `max_level` to stderr or to a log file. Nothing
+/// is ever written to stdout, as it is evaluated by the shell function.
+struct Logger {
+    max_level: Option<Level>,
+    log_file: Option<Mutex<File>>,
+    start: Instant,
+}
+
+impl Logger {
+    fn is_enabled(&self, level: Level) -> bool {
+        self.max_level.is_some_and(|max_level| level <= max_level)
+    }
+
+    fn log(&self, level: Level, message: &str) {
+        if !self.is_enabled(level) {
+            return;
+        }
+        let line = format!(
+            "[ohcrab {} {:>8.3}s] {message}\n",
+            level,
+            self.start.elapsed().as_secs_f64()
+        );
+        match &self.log_file {
+            Some(log_file) => {
+                let _ = log_file.lock().unwrap().write_all(line.as_bytes());
+            }
+            None => eprint!("{line}"),
+        }
+    }
+}
+
+/// Sets up the logger for the rest of the process. Only the first call has an effect.
+///
+/// * `max_level`: Most verbose level that is logged, `None` disables the logging.
+/// * `log_file`: File the messages are appended to. When absent, or if it cannot be
+///   opened, the messages are written to stderr.
+pub fn init(max_level: Option<Level>, log_file: Option<&Path>) {
+    let log_file = log_file.and_then(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| eprintln!("ohcrab: cannot open log file {}: {err}", path.display()))
+            .ok()
+    });
+    let _ = LOGGER.set(Logger {
+        max_level,
+        log_file: log_file.map(Mutex::new),
+        start: Instant::now(),
+    });
+}
+
+/// Returns `true` if the messages of this level are logged.
+pub fn is_enabled(level: Level) -> bool {
+    LOGGER.get().is_some_and(|logger| logger.is_enabled(level))
+}
+
+pub fn log(level: Level, message: &str) {
+    if let Some(logger) = LOGGER.get() {
+        logger.log(level, message);
+    }
+}
+
+pub fn error(message: &str) {
+    log(Level::Error, message);
+}
+
+pub fn warn(message: &str) {
+    log(Level::Warn, message);
+}
+
+pub fn info(message: &str) {
+    log(Level::Info, message);
+}
+
+pub fn debug(message: &str) {
+    log(Level::Debug, message);
+}
+
+/// Runs `phase` and logs how long it took, at the debug level.
+///
+/// * `name`: Name of the phase, only computed when the debug level is enabled.
+pub fn time<N, F, T>(name: N, phase: F) -> T
+where
+    N: FnOnce() -> String,
+    F: FnOnce() -> T,
+{
+    if !is_enabled(Level::Debug) {
+        return phase();
+    }
+    let start = Instant::now();
+    let result = phase();
+    debug(&format!("{} took {:.2?}", name(), start.elapsed()));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Level, Logger};
+    use rstest::rstest;
+    use std::io::Read;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    #[rstest]
+    #[case("1", Some(Level::Debug))]
+    #[case("true", Some(Level::Debug))]
+    #[case("DEBUG", Some(Level::Debug))]
+    #[case(" info ", Some(Level::Info))]
+    #[case("warning", Some(Level::Warn))]
+    #[case("error", Some(Level::Error))]
+    #[case("0", None)]
+    #[case("false", None)]
+    #[case("", None)]
+    fn test_level_from_env_value(#[case] value: &str, #[case] expected: Option<Level>) {
+        assert_eq!(Level::from_env_value(value), expected);
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let logger = Logger {
+            max_level: Some(Level::Info),
+            log_file: None,
+            start: Instant::now(),
+        };
+        assert!(logger.is_enabled(Level::Error));
+        assert!(logger.is_enabled(Level::Info));
+        assert!(!logger.is_enabled(Level::Debug));
+
+        let logger = Logger {
+            max_level: None,
+            log_file: None,
+            start: Instant::now(),
+        };
+        assert!(!logger.is_enabled(Level::Error));
+    }
+
+    #[test]
+    fn test_log_to_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let logger = Logger {
+            max_level: Some(Level::Info),
+            log_file: Some(Mutex::new(file.reopen().unwrap())),
+            start: Instant::now(),
+        };
+        logger.log(Level::Warn, "first message");
+        logger.log(Level::Debug, "hidden message");
+        logger.log(Level::Info, "second message");
+
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("[ohcrab WARN "));
+        assert!(lines[0].ends_with("s] first message"));
+        assert!(lines[1].starts_with("[ohcrab INFO "));
+        assert!(lines[1].ends_with("s] second message"));
+    }
+}
@@ -5,51 +5,140 @@
 #![allow(clippy::type_complexity)]
 
 pub mod cli;
+mod config;
+mod executables;
+mod logger;
 pub mod rules;
 pub mod shell;
 mod ui;
 mod utils;
 
+use clap::ArgMatches;
 use cli::{
-    command::run_command,
-    parser::{get_parser, prepare_arguments},
+    command::{prepare_command, run_command, CrabCommand},
+    instant_mode::read_last_output,
+    parser::{get_parser, prepare_arguments, RULES_SUBCOMMAND},
+};
+use config::Settings;
+use logger::{Level, ENV_VAR_DEBUG, ENV_VAR_LOG_FILE};
+use rules::{
+    explain_corrections, explain_rule, get_corrected_commands, list_rules, rules_require_output,
+    translate_to_english,
 };
-use rules::get_corrected_commands;
 use shell::{get_bash_type, Shell};
-use std::env;
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::{env, process};
 
-use crate::{ui::interactive_menu, utils::debug_log};
+use crate::ui::interactive_menu;
 
 const ARGUMENT_PLACEHOLDER: &str = "OHCRAB_ARGUMENT_PLACEHOLDER";
 const ENV_VAR_NAME_HISTORY: &str = "OHCRAB_COMMAND_HISTORY";
 const ENV_VAR_NAME_ALIAS: &str = "OHCRAB_ALIAS";
 const ENV_VAR_NAME_SHELL: &str = "OHCRAB_SHELL";
+const ENV_VAR_NAME_SHELL_ALIASES: &str = "OHCRAB_SHELL_ALIASES";
+const ENV_VAR_NAME_OUTPUT_LOG: &str = "OHCRAB_OUTPUT_LOG";
+
+/// Translates the known localized messages of the output to English, as the rules
+/// match English messages.
+fn translate_output(crab_command: &mut CrabCommand) {
+    let outputs = [
+        &mut crab_command.output,
+        &mut crab_command.stdout,
+        &mut crab_command.stderr,
+    ];
+    for text in outputs.into_iter().flatten() {
+        *text = translate_to_english(text);
+    }
+    for chunk in &mut crab_command.output_chunks {
+        chunk.text = translate_to_english(&chunk.text);
+    }
+}
 
 /// Handles the command correction logic.
 fn handle_command_correction(
     command_values: clap::parser::Values<String>,
     system_shell: &dyn Shell,
+    settings: &Settings,
     select_first: bool,
+    json_output: bool,
+    explain: bool,
 ) {
     let command_vec: Vec<String> = command_values.collect();
-    debug_log(&format!("Retrieved command(s): {:?}", command_vec));
+    logger::debug(&format!("Retrieved command(s): {:?}", command_vec));
 
-    let mut crab_command = run_command(command_vec, system_shell);
-    debug_log(&format!("Crab command: {:?}", crab_command));
+    let mut crab_command = if rules_require_output(settings) {
+        match read_last_output(&command_vec.join(" "), settings.max_output_size) {
+            Some(output) => {
+                logger::debug("Output read from the session log, skipping the command execution");
+                CrabCommand::new(
+                    prepare_command(command_vec, system_shell),
+                    Some(output),
+                    None,
+                )
+            }
+            None => logger::time(
+                || "Command re-run".to_owned(),
+                || run_command(command_vec, system_shell, settings),
+            ),
+        }
+    } else {
+        logger::debug("No enabled rule requires output, skipping the command execution");
+        CrabCommand::new(prepare_command(command_vec, system_shell), None, None)
+    };
+    translate_output(&mut crab_command);
+    logger::debug(&format!("Crab command: {:?}", crab_command));
+
+    if explain {
+        // Printed on stderr, so the shell function has nothing to evaluate
+        eprint!(
+            "{}",
+            explain_corrections(&mut crab_command, system_shell, settings)
+        );
+        return;
+    }
+
+    if crab_command.succeeded() {
+        eprintln!(
+            "ohcrab: `{}` ran successfully, nothing to correct",
+            crab_command.script
+        );
+        if json_output {
+            println!("[]");
+        }
+        return;
+    }
 
-    let corrected_commands = get_corrected_commands(&mut crab_command, system_shell);
-    debug_log(&format!(
+    let corrected_commands = logger::time(
+        || "Rules".to_owned(),
+        || get_corrected_commands(&mut crab_command, system_shell, settings),
+    );
+    logger::debug(&format!(
         "Candidate command(s): {:?}",
         corrected_commands
             .iter()
-            .map(|cmd| cmd.script.to_owned())
+            .map(|cmd| format!(
+                "{} (priority: {}, rules: {})",
+                cmd.script,
+                cmd.priority,
+                cmd.rule_names.join(", ")
+            ))
             .collect::<Vec<_>>()
     ));
 
+    if json_output {
+        // Side effects are not run: the caller decides what to do with the candidates
+        println!("{}", serde_json::to_string(&corrected_commands).unwrap());
+        return;
+    }
+
     let selected_command = if select_first {
-        corrected_commands.first()
+        corrected_commands.first().map(Cow::Borrowed)
     } else {
-        interactive_menu(&corrected_commands)
+        logger::time(
+            || "Menu".to_owned(),
+            || interactive_menu(&corrected_commands),
+        )
     };
 
     if let Some(valid_command) = selected_command {
@@ -57,23 +146,90 @@ fn handle_command_correction(
         if !select_first {
             eprintln!();
         }
-        debug_log(&format!("Command selected: {:?}", valid_command));
+        logger::debug(&format!("Command selected: {:?}", valid_command));
         valid_command.run(crab_command);
     }
 }
 
+/// Handles the `rules` subcommand: lists the rules or, with `rules explain`, runs a
+/// single rule against a command.
+fn handle_rules_subcommand(
+    rules_matches: &ArgMatches,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+) {
+    let Some(("explain", explain_matches)) = rules_matches.subcommand() else {
+        print!("{}", list_rules(settings));
+        return;
+    };
+    let rule_name = explain_matches.get_one::<String>("name").unwrap();
+    let command_vec: Vec<String> = explain_matches
+        .get_many::<String>("command")
+        .unwrap()
+        .cloned()
+        .collect();
+    let mut crab_command = match explain_matches.get_one::<String>("output") {
+        Some(output) => CrabCommand::new(
+            prepare_command(command_vec, system_shell),
+            Some(output.to_owned()),
+            None,
+        ),
+        None => run_command(command_vec, system_shell, settings),
+    };
+    translate_output(&mut crab_command);
+    match explain_rule(rule_name, &mut crab_command, system_shell, settings) {
+        Ok(explanation) => print!("{explanation}"),
+        Err(err) => {
+            eprintln!("ohcrab: {err}");
+            process::exit(1);
+        }
+    }
+}
+
 fn main() {
     // Skip the first element of `env::args()` (the name of program)
     let args: Vec<String> = env::args().skip(1).collect();
     let args = prepare_arguments(args);
     let mut arg_matches = get_parser().get_matches_from(args);
+    let log_level = if arg_matches.get_flag("debug") {
+        Some(Level::Debug)
+    } else {
+        // The warnings (e.g. an invalid configuration file) are shown by default
+        env::var(ENV_VAR_DEBUG)
+            .ok()
+            .map_or(Some(Level::Warn), |value| Level::from_env_value(&value))
+    };
+    let log_file = env::var_os(ENV_VAR_LOG_FILE).map(PathBuf::from);
+    logger::init(log_level, log_file.as_deref());
     let system_shell = get_bash_type(&arg_matches.remove_one::<String>("shell").unwrap());
     let select_first = arg_matches.get_flag("select-first");
+    let json_output = arg_matches.get_one::<String>("format").unwrap() == "json";
+    let explain = arg_matches.get_flag("explain");
 
-    if let Some(command) = arg_matches.remove_many::<String>("command") {
-        handle_command_correction(command, &*system_shell, select_first);
+    if let Some((RULES_SUBCOMMAND, rules_matches)) = arg_matches.subcommand() {
+        let settings = Settings::load();
+        handle_rules_subcommand(rules_matches, &*system_shell, &settings);
+    } else if let Some(command) = arg_matches.remove_many::<String>("command") {
+        let settings = Settings::load();
+        handle_command_correction(
+            command,
+            &*system_shell,
+            &settings,
+            select_first,
+            json_output,
+            explain,
+        );
     } else {
         let alias_name = arg_matches.get_one::<String>("alias").unwrap();
         println!("{}", system_shell.app_alias(alias_name));
+        if arg_matches.get_flag("enable-instant-mode") {
+            match system_shell.instant_mode_alias() {
+                Some(instant_mode_alias) => println!("{instant_mode_alias}"),
+                None => eprintln!(
+                    "ohcrab: instant mode is not supported by {}",
+                    system_shell.get_shell()
+                ),
+            }
+        }
     }
 }
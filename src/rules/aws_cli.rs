@@ -1,6 +1,6 @@
+use super::utils::patterns::{AWS_CLI_INVALID_CHOICE, AWS_CLI_OPTIONS};
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -16,14 +16,12 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re_invalid_choice = Regex::new(r"Invalid choice: '(.*)', maybe you meant:").unwrap();
-        let re_options = Regex::new(r"(?m)^\s*\*\s(.*)").unwrap();
-        let mistake = re_invalid_choice
+        let mistake = AWS_CLI_INVALID_CHOICE
             .captures(output)
             .unwrap()
             .get(1)
             .map_or("", |m| m.as_str());
-        let options = re_options
+        let options = AWS_CLI_OPTIONS
             .captures_iter(output)
             .map(|cap| cap[1].to_string())
             .collect::<Vec<_>>();
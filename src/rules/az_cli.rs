@@ -1,6 +1,6 @@
+use super::utils::patterns::{AZ_CLI_MISTAKE, AZ_CLI_OPTIONS};
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -15,14 +15,12 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    let re_mistake = Regex::new(r"az(?:.*): '(.*)' is not in the '.*' command group.").unwrap();
-    let re_options = Regex::new(r"(?m)^The most similar choice to '.*' is:\n\s*(.*)$").unwrap();
-    let mistake = re_mistake
+    let mistake = AZ_CLI_MISTAKE
         .captures(command.output.as_ref().unwrap())
         .unwrap()
         .get(1)
         .map_or("", |m| m.as_str());
-    let options = re_options
+    let options = AZ_CLI_OPTIONS
         .captures_iter(command.output.as_ref().unwrap())
         .map(|cap| cap[1].to_owned())
         .collect::<Vec<_>>();
@@ -1,5 +1,5 @@
+use super::utils::patterns::BREW_INSTALL_DID_YOU_MEAN;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 use super::Rule;
 
@@ -21,12 +21,8 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    let re = Regex::new(
-        "Warning: No available formula with the name \"(?:[^\"]+)\". Did you mean (.+)\\?",
-    )
-    .unwrap();
     let stdout = &command.output.as_ref().unwrap();
-    let caps = re.captures(stdout).unwrap();
+    let caps = BREW_INSTALL_DID_YOU_MEAN.captures(stdout).unwrap();
     let suggestions = get_suggestions(caps.get(1).map_or("", |m| m.as_str()).to_owned());
     suggestions
         .iter()
@@ -1,14 +1,12 @@
+use super::utils::patterns::{BREW_ALREADY_INSTALLED, BREW_REINSTALL_HINT};
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
-        let warning_regex = Regex::new(r"Warning: .+ is already installed and up-to-date").unwrap();
-        let message_regex = Regex::new(r"To reinstall .+, run `brew reinstall [^`]+`").unwrap();
         command.script.contains("install")
-            && warning_regex.is_match(output)
-            && message_regex.is_match(output)
+            && BREW_ALREADY_INSTALLED.is_match(output)
+            && BREW_REINSTALL_HINT.is_match(output)
     } else {
         false
     }
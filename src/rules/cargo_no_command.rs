@@ -1,6 +1,6 @@
+use super::utils::patterns::CARGO_DID_YOU_MEAN;
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -17,9 +17,8 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    let re = Regex::new(r"Did you mean `([^`]*)`").unwrap();
     let broken = command.script_parts[1].clone();
-    let fix = re
+    let fix = CARGO_DID_YOU_MEAN
         .captures(command.output.as_ref().unwrap())
         .unwrap()
         .get(1)
@@ -1,6 +1,8 @@
+use crate::config::Settings;
 use crate::rules::cd_mkdir::auxiliary_get_new_command;
 use crate::utils::get_close_matches;
 use crate::{cli::command::CrabCommand, shell::Shell};
+use std::sync::Arc;
 
 use std::env;
 use std::fs;
@@ -35,7 +37,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     match_rule_without_sudo(auxiliary_match_rule, command)
 }
 
-fn _get_new_command(command: &CrabCommand) -> Vec<String> {
+fn _get_new_command(command: &CrabCommand, settings: &Settings) -> Vec<String> {
     if command.script_parts.len() > 1 {
         let mut dest: Vec<&str> = command.script_parts[1].split(MAIN_SEPARATOR).collect();
         let mut cwd;
@@ -63,7 +65,12 @@ fn _get_new_command(command: &CrabCommand) -> Vec<String> {
             }
             let sub_dirs = get_sub_dirs(&cwd);
             let sub_dirs = sub_dirs.iter().map(|s| s.as_str()).collect::<Vec<&str>>();
-            let best_matches = get_close_matches(directory, &sub_dirs, None, None);
+            let best_matches = get_close_matches(
+                directory,
+                &sub_dirs,
+                settings.num_close_matches,
+                settings.close_match_cutoff,
+            );
             if !best_matches.is_empty() {
                 cwd = Path::new(&cwd)
                     .join(best_matches[0])
@@ -79,18 +86,22 @@ fn _get_new_command(command: &CrabCommand) -> Vec<String> {
     vec![]
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    get_new_command_without_sudo(_get_new_command, command)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    get_new_command_without_sudo(|command| _get_new_command(command, settings), command)
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "cd_correction".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -1,5 +1,5 @@
+use super::utils::patterns::CD_COMMAND;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 use super::{
     get_new_command_without_sudo, match_rule_without_sudo, utils::match_rule_with_is_app, Rule,
@@ -25,9 +25,8 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
-    let re = Regex::new(r"^cd (.*)").unwrap();
     let repl = |caps: &regex::Captures| format!("mkdir -p {} && cd {}", &caps[1], &caps[1]);
-    vec![re.replace(&command.script, repl).to_string()]
+    vec![CD_COMMAND.replace(&command.script, repl).to_string()]
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
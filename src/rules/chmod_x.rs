@@ -1,4 +1,7 @@
-use crate::{cli::command::CrabCommand, shell::Shell};
+use crate::{
+    cli::command::{CrabCommand, EXIT_CODE_CANNOT_EXECUTE},
+    shell::Shell,
+};
 use is_executable::IsExecutable;
 
 use std::path::Path;
@@ -11,7 +14,10 @@ fn _match_rule(
     mock_file_access: Option<bool>,
 ) -> bool {
     if let Some(stdout) = &command.output {
-        command.script.starts_with("./")
+        command
+            .exit_code
+            .map_or(true, |code| code == EXIT_CODE_CANNOT_EXECUTE)
+            && command.script.starts_with("./")
             && stdout.to_lowercase().contains("permission denied")
             && {
                 if let Some(file_exists) = mock_file_exists {
@@ -126,6 +132,19 @@ mod tests {
         unmatch_rule_4: ("gradlew build", "gradlew: Permission denied", Some(true), Some(false)),
     }
 
+    #[test]
+    fn test_match_exit_code() {
+        let command = CrabCommand::new(
+            "./gradlew build".to_owned(),
+            Some("gradlew: Permission denied".to_owned()),
+            None,
+        );
+        let mut cannot_execute = command.clone().with_exit_status(Some(126), None);
+        assert!(_match_rule(&mut cannot_execute, Some(true), Some(false)));
+        let mut failed = command.with_exit_status(Some(1), None);
+        assert!(!_match_rule(&mut failed, Some(true), Some(false)));
+    }
+
     parameterized_get_new_command_tests! {
         get_new_command_1: ("./gradlew build", "", "chmod +x gradlew && ./gradlew build"),
         get_new_command_2: ("./install.sh --help", "", "chmod +x install.sh && ./install.sh --help"),
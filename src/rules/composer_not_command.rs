@@ -1,6 +1,8 @@
+use super::utils::patterns::{
+    COMPOSER_BROKEN_COMMAND, COMPOSER_DID_YOU_MEAN_ONE_OF, COMPOSER_DID_YOU_MEAN_THIS,
+};
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -20,9 +22,6 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re_broken_cmd = Regex::new(r#"Command "([^']*)" is not defined"#).unwrap();
-        let re_new_cmd = Regex::new(r"Did you mean this\?[^\n]*\n\s*([^\n]*)").unwrap();
-        let re_new_cmd_alt = Regex::new(r"Did you mean one of these\?[^\n]*\n\s*([^\n]*)").unwrap();
         let broken_cmd;
         let mut new_cmd;
         if command.script_parts.contains(&"install".to_owned())
@@ -31,14 +30,14 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
             broken_cmd = "install".to_owned();
             new_cmd = "require".to_owned();
         } else {
-            if let Some(captures) = re_broken_cmd.captures(output) {
+            if let Some(captures) = COMPOSER_BROKEN_COMMAND.captures(output) {
                 broken_cmd = captures.get(1).map_or("", |m| m.as_str()).to_owned();
             } else {
                 return Vec::<String>::new();
             }
-            if let Some(captures) = re_new_cmd.captures(output) {
+            if let Some(captures) = COMPOSER_DID_YOU_MEAN_THIS.captures(output) {
                 new_cmd = captures.get(1).map_or("", |m| m.as_str()).to_owned();
-            } else if let Some(captures) = re_new_cmd_alt.captures(output) {
+            } else if let Some(captures) = COMPOSER_DID_YOU_MEAN_ONE_OF.captures(output) {
                 new_cmd = captures.get(1).map_or("", |m| m.as_str()).to_owned();
             } else {
                 return Vec::<String>::new();
@@ -1,6 +1,6 @@
+use super::utils::patterns::CONDA_MISTYPE;
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -16,8 +16,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re = Regex::new(r"'conda ([^']*)'").unwrap();
-        let matches = re
+        let matches = CONDA_MISTYPE
             .captures_iter(output)
             .map(|cap| cap[1].to_owned())
             .collect::<Vec<_>>();
@@ -1,8 +1,8 @@
+use super::utils::patterns::CP_COMMAND;
 use super::{
     get_new_command_without_sudo, match_rule_without_sudo, utils::match_rule_with_is_app, Rule,
 };
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -21,8 +21,9 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
-    let re = Regex::new(r"^cp ").unwrap();
-    vec![re.replace_all(&command.script, "cp -a ").into_owned()]
+    vec![CP_COMMAND
+        .replace_all(&command.script, "cp -a ")
+        .into_owned()]
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
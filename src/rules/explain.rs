@@ -0,0 +1,522 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use super::{get_rules, organize_commands, run_rules, Rule, RuleVerdict};
+use crate::cli::command::{CorrectedCommand, CrabCommand};
+use crate::config::Settings;
+use crate::shell::Shell;
+
+fn format_exit_status(command: &CrabCommand) -> String {
+    match (command.exit_code, command.signal) {
+        (Some(code), _) => code.to_string(),
+        (None, Some(signal)) => format!("killed by signal {signal}"),
+        (None, None) => "<unknown>".to_owned(),
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Returns a table with all the rules (built-in, user rules and plugins), their
+/// priority, whether they need the output of the command and whether they are enabled
+/// with the given settings.
+pub fn list_rules(settings: &Settings) -> String {
+    format_rules_table(&get_rules(), settings)
+}
+
+fn format_rules_table(rules: &[Rule], settings: &Settings) -> String {
+    let name_width = rules
+        .iter()
+        .map(|rule| rule.name.len())
+        .chain(["NAME".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut table = format!(
+        "{:name_width$}  {:>8}  {:<15}  {}\n",
+        "NAME", "PRIORITY", "REQUIRES OUTPUT", "ENABLED"
+    );
+    for rule in rules {
+        let priority = settings.priority.get(&rule.name).unwrap_or(&rule.priority);
+        let _ = writeln!(
+            table,
+            "{:name_width$}  {:>8}  {:<15}  {}",
+            rule.name,
+            priority,
+            yes_no(rule.requires_output),
+            yes_no(rule.is_enabled(settings))
+        );
+    }
+    table
+}
+
+/// Runs the rule called `rule_name` against the command and describes the result:
+/// whether the rule is enabled, whether it matches (or why it does not) and the
+/// commands it proposes.
+///
+/// Returns an error if there is no rule with this name.
+pub fn explain_rule(
+    rule_name: &str,
+    command: &mut CrabCommand,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+) -> Result<String, String> {
+    let rule = get_rules()
+        .into_iter()
+        .find(|rule| rule.name == rule_name)
+        .ok_or(format!("unknown rule `{rule_name}`"))?;
+    Ok(format_explanation(&rule, command, system_shell, settings))
+}
+
+fn format_explanation(
+    rule: &Rule,
+    command: &mut CrabCommand,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+) -> String {
+    let mut explanation = String::new();
+    let _ = writeln!(explanation, "Rule: {}", rule.name);
+    let _ = writeln!(
+        explanation,
+        "Enabled: {}",
+        yes_no(rule.is_enabled(settings))
+    );
+    let _ = writeln!(
+        explanation,
+        "Requires output: {}",
+        yes_no(rule.requires_output)
+    );
+    let _ = writeln!(explanation, "Script: {}", command.script);
+    let _ = writeln!(explanation, "Exit status: {}", format_exit_status(command));
+    let _ = writeln!(
+        explanation,
+        "Output: {}",
+        command.output.as_deref().unwrap_or("<none>").trim_end()
+    );
+
+    if command.output.is_none() && rule.requires_output {
+        let _ = writeln!(
+            explanation,
+            "Match: no, the rule requires the output of the command"
+        );
+        return explanation;
+    }
+    // Like in the correction, the rule cannot crash or block the explanation
+    let budget = Duration::from_millis(settings.rule_budget);
+    match run_rules(vec![rule.clone()], command, system_shell, settings, budget).pop() {
+        Some(RuleVerdict::Matched(corrected_commands)) => {
+            let _ = writeln!(explanation, "Match: yes");
+            if corrected_commands.is_empty() {
+                let _ = writeln!(explanation, "Corrected commands: none");
+            } else {
+                let _ = writeln!(explanation, "Corrected commands:");
+                for corrected in corrected_commands {
+                    let _ = writeln!(
+                        explanation,
+                        "  {} (priority: {}{})",
+                        corrected.script,
+                        corrected.priority,
+                        if corrected.side_effect.is_some() {
+                            ", +side_effect"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+            }
+            if !rule.is_enabled(settings) {
+                let _ = writeln!(
+                    explanation,
+                    "The rule is disabled, so these commands are not proposed"
+                );
+            }
+        }
+        Some(RuleVerdict::NotMatched) => {
+            let _ = writeln!(
+                explanation,
+                "Match: no, the rule does not apply to this script and output"
+            );
+        }
+        Some(RuleVerdict::Panicked) => {
+            let _ = writeln!(explanation, "Match: no, the rule panicked");
+        }
+        Some(RuleVerdict::TimedOut) | None => {
+            let _ = writeln!(
+                explanation,
+                "Match: no, the rule timed out after {budget:?}"
+            );
+        }
+    }
+    explanation
+}
+
+/// Runs every rule against the command and describes the whole correction: the
+/// verdict of each rule with the commands it proposes, the final ordering of the
+/// commands and the duplicated commands that were dropped.
+pub fn explain_corrections(
+    command: &mut CrabCommand,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+) -> String {
+    format_corrections_explanation(&get_rules(), command, system_shell, settings)
+}
+
+fn format_corrections_explanation(
+    rules: &[Rule],
+    command: &mut CrabCommand,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+) -> String {
+    let runnable = |rule: &Rule| {
+        rule.is_enabled(settings) && (command.output.is_some() || !rule.requires_output)
+    };
+    // Like in the correction, each rule works on its own copy of the command and a
+    // rule cannot crash or block the explanation
+    let budget = Duration::from_millis(settings.rule_budget);
+    let runnable_rules: Vec<Rule> = rules
+        .iter()
+        .filter(|rule| runnable(rule))
+        .cloned()
+        .collect();
+    let mut rule_verdicts =
+        run_rules(runnable_rules, command, system_shell, settings, budget).into_iter();
+
+    let mut verdicts: Vec<(&str, &str, Vec<CorrectedCommand>)> = vec![];
+    for rule in rules {
+        let verdict = if !rule.is_enabled(settings) {
+            ("disabled", vec![])
+        } else if !runnable(rule) {
+            ("no output", vec![])
+        } else {
+            match rule_verdicts.next() {
+                Some(RuleVerdict::Matched(corrected_commands)) => ("yes", corrected_commands),
+                Some(RuleVerdict::NotMatched) => ("no", vec![]),
+                Some(RuleVerdict::Panicked) => ("panicked", vec![]),
+                Some(RuleVerdict::TimedOut) | None => ("timed out", vec![]),
+            }
+        };
+        verdicts.push((&rule.name, verdict.0, verdict.1));
+    }
+
+    let name_width = verdicts
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .chain(["RULE".len()])
+        .max()
+        .unwrap_or_default();
+    let mut explanation = format!("{:name_width$}  {:<9}  CANDIDATES\n", "RULE", "MATCH");
+    for (name, verdict, candidates) in &verdicts {
+        let candidates: Vec<String> = candidates
+            .iter()
+            .map(|cmd| format!("{} ({})", cmd.script, cmd.priority))
+            .collect();
+        let row = format!(
+            "{:name_width$}  {:<9}  {}",
+            name,
+            verdict,
+            candidates.join("; ")
+        );
+        let _ = writeln!(explanation, "{}", row.trim_end());
+    }
+
+    let mut candidates: Vec<CorrectedCommand> = verdicts
+        .into_iter()
+        .flat_map(|(_, _, candidates)| candidates)
+        .collect();
+    candidates.sort_by_key(|cmd| cmd.priority);
+    let dropped: Vec<(String, u16, String)> = candidates
+        .iter()
+        .map(|cmd| {
+            (
+                cmd.script.to_owned(),
+                cmd.priority,
+                cmd.rule_name.to_owned(),
+            )
+        })
+        .collect();
+    let organized = organize_commands(candidates);
+
+    let _ = writeln!(explanation, "\nFinal ordering:");
+    if organized.is_empty() {
+        let _ = writeln!(explanation, "  none");
+    }
+    for (n, cmd) in organized.iter().enumerate() {
+        let _ = writeln!(
+            explanation,
+            "  {}. {} (priority: {}, rules: {})",
+            n + 1,
+            cmd.script,
+            cmd.priority,
+            cmd.rule_names.join(", ")
+        );
+    }
+
+    // Every candidate that is not the command kept for its script was dropped
+    let dropped: Vec<_> = dropped
+        .into_iter()
+        .filter(|(script, priority, rule_name)| {
+            !organized.iter().any(|kept| {
+                kept.script == *script && kept.priority == *priority && kept.rule_name == *rule_name
+            })
+        })
+        .collect();
+    let _ = writeln!(explanation, "\nDropped duplicates:");
+    if dropped.is_empty() {
+        let _ = writeln!(explanation, "  none");
+    }
+    for (script, priority, rule_name) in dropped {
+        let _ = writeln!(
+            explanation,
+            "  {script} (priority: {priority}, rule: {rule_name})"
+        );
+    }
+    explanation
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{
+        explain_rule, format_corrections_explanation, format_explanation, format_rules_table,
+        list_rules,
+    };
+    use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
+    use crate::rules::Rule;
+    use crate::shell::Bash;
+
+    const GIT_BRNCH_OUTPUT: &str = "git: 'brnch' is not a git command. See 'git --help'.\n\n\
+        The most similar command is\n\tbranch\n";
+
+    #[test]
+    fn test_format_rules_table() {
+        let rules = vec![
+            Rule::new(
+                "a_long_rule_name".to_owned(),
+                None,
+                Some(900),
+                None,
+                |_, _| true,
+                |_, _| vec![],
+                None,
+            ),
+            Rule::new(
+                "short".to_owned(),
+                Some(false),
+                None,
+                Some(false),
+                |_, _| true,
+                |_, _| vec![],
+                None,
+            ),
+        ];
+        let mut settings = Settings::default();
+        settings.priority.insert("short".to_owned(), 5);
+
+        assert_eq!(
+            format_rules_table(&rules, &settings),
+            "NAME              PRIORITY  REQUIRES OUTPUT  ENABLED\n\
+            a_long_rule_name       900  yes              yes\n\
+            short                    5  no               no\n"
+        );
+    }
+
+    #[test]
+    fn test_list_rules() {
+        let table = list_rules(&Settings::default());
+        assert!(table.starts_with("NAME "));
+        assert!(table
+            .lines()
+            .any(|line| line.starts_with("git_push_force ") && line.ends_with(" no")));
+    }
+
+    #[test]
+    fn test_explain_rule_match() {
+        let mut command = CrabCommand::new(
+            "git brnch".to_owned(),
+            Some(GIT_BRNCH_OUTPUT.to_owned()),
+            None,
+        );
+        let explanation = explain_rule(
+            "git_not_command",
+            &mut command,
+            &Bash {},
+            &Settings::default(),
+        )
+        .unwrap();
+        assert!(explanation.starts_with("Rule: git_not_command\nEnabled: yes\n"));
+        assert!(explanation.contains("Match: yes\nCorrected commands:\n  git branch (priority: "));
+    }
+
+    #[test]
+    fn test_explain_rule_no_match() {
+        let mut command = CrabCommand::new("git brnch".to_owned(), Some("".to_owned()), None);
+        let explanation = explain_rule(
+            "git_not_command",
+            &mut command,
+            &Bash {},
+            &Settings::default(),
+        )
+        .unwrap();
+        assert!(explanation.contains("Match: no, the rule does not apply"));
+
+        let mut command = CrabCommand::new("git brnch".to_owned(), None, None);
+        let explanation = explain_rule(
+            "git_not_command",
+            &mut command,
+            &Bash {},
+            &Settings::default(),
+        )
+        .unwrap();
+        assert!(explanation.contains("Output: <none>\n"));
+        assert!(explanation.contains("Match: no, the rule requires the output"));
+    }
+
+    #[test]
+    fn test_explain_rule_disabled() {
+        let settings = Settings {
+            disabled_rules: vec!["git_not_command".to_owned()],
+            ..Settings::default()
+        };
+        let mut command = CrabCommand::new(
+            "git brnch".to_owned(),
+            Some(GIT_BRNCH_OUTPUT.to_owned()),
+            None,
+        );
+        let explanation =
+            explain_rule("git_not_command", &mut command, &Bash {}, &settings).unwrap();
+        assert!(explanation.contains("Enabled: no\n"));
+        assert!(explanation.ends_with("The rule is disabled, so these commands are not proposed\n"));
+    }
+
+    #[test]
+    fn test_explain_unknown_rule() {
+        let mut command = CrabCommand::new("ls".to_owned(), None, None);
+        assert_eq!(
+            explain_rule("not_a_rule", &mut command, &Bash {}, &Settings::default()),
+            Err("unknown rule `not_a_rule`".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_format_corrections_explanation() {
+        let rules = vec![
+            Rule::new(
+                "first".to_owned(),
+                None,
+                Some(100),
+                Some(false),
+                |_, _| true,
+                |_, _| vec!["ls -a".to_owned(), "ls -l".to_owned()],
+                None,
+            ),
+            Rule::new(
+                "second".to_owned(),
+                None,
+                Some(150),
+                Some(false),
+                |_, _| true,
+                |_, _| vec!["ls -l".to_owned()],
+                None,
+            ),
+            Rule::new(
+                "no_match".to_owned(),
+                None,
+                None,
+                Some(false),
+                |_, _| false,
+                |_, _| vec!["unused".to_owned()],
+                None,
+            ),
+            Rule::new(
+                "with_output".to_owned(),
+                None,
+                None,
+                None,
+                |_, _| true,
+                |_, _| vec!["unused".to_owned()],
+                None,
+            ),
+            Rule::new(
+                "disabled".to_owned(),
+                Some(false),
+                None,
+                Some(false),
+                |_, _| true,
+                |_, _| vec!["unused".to_owned()],
+                None,
+            ),
+            Rule::from_closures(
+                "broken".to_owned(),
+                None,
+                None,
+                Some(false),
+                Arc::new(|_, _, _| panic!("broken rule")),
+                Arc::new(|_, _, _| vec!["unused".to_owned()]),
+                None,
+            ),
+        ];
+        let mut command = CrabCommand::new("ls".to_owned(), None, None);
+
+        assert_eq!(
+            format_corrections_explanation(&rules, &mut command, &Bash {}, &Settings::default()),
+            "RULE         MATCH      CANDIDATES\n\
+            first        yes        ls -a (100); ls -l (200)\n\
+            second       yes        ls -l (150)\n\
+            no_match     no\n\
+            with_output  no output\n\
+            disabled     disabled\n\
+            broken       panicked\n\
+            \n\
+            Final ordering:\n  \
+            1. ls -a (priority: 100, rules: first)\n  \
+            2. ls -l (priority: 150, rules: second, first)\n\
+            \n\
+            Dropped duplicates:\n  \
+            ls -l (priority: 200, rule: first)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_explanation_broken_rule() {
+        let rule = Rule::from_closures(
+            "broken".to_owned(),
+            None,
+            None,
+            Some(false),
+            Arc::new(|_, _, _| panic!("broken rule")),
+            Arc::new(|_, _, _| vec![]),
+            None,
+        );
+        let mut command = CrabCommand::new("ls".to_owned(), None, None);
+        let explanation = format_explanation(&rule, &mut command, &Bash {}, &Settings::default());
+        assert!(explanation.ends_with("Match: no, the rule panicked\n"));
+
+        let rule = Rule::from_closures(
+            "stuck".to_owned(),
+            None,
+            None,
+            Some(false),
+            Arc::new(|_, _, _| {
+                thread::sleep(Duration::from_secs(10));
+                true
+            }),
+            Arc::new(|_, _, _| vec![]),
+            None,
+        );
+        let settings = Settings {
+            rule_budget: 100,
+            ..Settings::default()
+        };
+        let explanation = format_explanation(&rule, &mut command, &Bash {}, &settings);
+        assert!(explanation.ends_with("Match: no, the rule timed out after 100ms\n"));
+    }
+}
@@ -1,3 +1,4 @@
+use super::utils::patterns::GIT_ADD_MISSING_FILE;
 use crate::{
     cli::command::CrabCommand,
     rules::{
@@ -6,16 +7,12 @@ use crate::{
     },
     shell::Shell,
 };
-use regex::Regex;
 use shlex::Quoter; // Import the Quoter
 use std::path::Path;
 
 fn get_missing_file(command: &CrabCommand, path_exists: Option<bool>) -> Option<String> {
     if let Some(stdout) = &command.output {
-        let re = Regex::new(r"error: pathspec '([^']*)' did not match any file\(s\) known to git.")
-            .unwrap();
-
-        if let Some(captures) = re.captures(stdout) {
+        if let Some(captures) = GIT_ADD_MISSING_FILE.captures(stdout) {
             let path = &captures[1];
             if path.is_empty() {
                 None
@@ -1,9 +1,11 @@
+use super::utils::patterns::{GIT_BISECT_BROKEN, GIT_BISECT_USAGE};
 use super::{utils::git::get_new_command_with_git_support, Rule};
+use crate::config::Settings;
 use crate::utils::replace_command;
 use crate::{
     cli::command::CrabCommand, rules::utils::git::match_rule_with_git_support, shell::Shell,
 };
-use regex::Regex;
+use std::sync::Arc;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
@@ -20,15 +22,13 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 fn auxiliary_get_new_command(
     command: &CrabCommand,
     system_shell: Option<&dyn Shell>,
+    settings: &Settings,
 ) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let re_broken = Regex::new(r"git bisect ([^ $]*).*").unwrap();
-        let re_usage = Regex::new(r"usage: git bisect \[([^\]]+)\]").unwrap();
-
-        let broken = re_broken.captures(&command.script);
-        let usage = re_usage.captures(stdout);
+        let broken = GIT_BISECT_BROKEN.captures(&command.script);
+        let usage = GIT_BISECT_USAGE.captures(stdout);
         if let (Some(broken), Some(usage)) = (broken, usage) {
-            replace_command(command, &broken[1], usage[1].split('|').collect())
+            replace_command(command, &broken[1], usage[1].split('|').collect(), settings)
         } else {
             Vec::<String>::new()
         }
@@ -37,18 +37,26 @@ fn auxiliary_get_new_command(
     }
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    get_new_command_with_git_support(auxiliary_get_new_command, command, system_shell)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    get_new_command_with_git_support(
+        |command, system_shell| auxiliary_get_new_command(command, system_shell, settings),
+        command,
+        system_shell,
+    )
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "git_bisect_usage".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -57,6 +65,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{get_new_command, match_rule};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use crate::shell::Bash;
 
     const OUTPUT: &str = "usage: git bisect [help|start|bad|good|new|old|terms|skip|next|reset|visualize|replay|log|run]";
@@ -91,7 +100,7 @@ mod tests {
             .map(|s| format!("git bisect {s}"))
             .collect::<Vec<_>>();
         assert_eq!(
-            get_new_command(&mut command, Some(&system_shell)),
+            get_new_command(&mut command, Some(&system_shell), &Settings::default()),
             new_command
         );
     }
@@ -1,8 +1,8 @@
+use super::utils::patterns::GIT_BRANCH_EXISTS;
 use super::{utils::git::get_new_command_with_git_support, Rule};
 use crate::{
     cli::command::CrabCommand, rules::utils::git::match_rule_with_git_support, shell::Shell,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
@@ -21,8 +21,7 @@ fn auxiliary_get_new_command(
     system_shell: Option<&dyn Shell>,
 ) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let re_branch_name = Regex::new(r"fatal: A branch named '(.+)' already exists.").unwrap();
-        if let Some(captures) = re_branch_name.captures(stdout) {
+        if let Some(captures) = GIT_BRANCH_EXISTS.captures(stdout) {
             let mut new_commands = Vec::<String>::new();
             let branch_name = &captures[1].replace('\'', r"\'");
             let new_command_templates = vec![
@@ -1,10 +1,10 @@
+use super::utils::patterns::GIT_CHECKOUT_MISSING_PATHSPEC;
 use super::{utils::git::get_new_command_with_git_support, Rule};
 use crate::utils::replace_argument;
 use crate::{
     cli::command::CrabCommand, rules::utils::git::match_rule_with_git_support, shell::Shell,
     utils::get_closest,
 };
-use regex::Regex;
 
 use std::process::Command;
 use std::str;
@@ -60,9 +60,7 @@ fn mockable_get_new_command(
     mock_output: Option<&str>,
 ) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let re = Regex::new(r"error: pathspec '([^']*)' did not match any file\(s\) known to git")
-            .unwrap();
-        if let Some(caps) = re.captures(stdout) {
+        if let Some(caps) = GIT_CHECKOUT_MISSING_PATHSPEC.captures(stdout) {
             let missing_file = caps.get(1).map_or("", |m| m.as_str());
 
             let branches = get_branches(mock_output);
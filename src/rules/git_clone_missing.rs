@@ -1,5 +1,5 @@
+use super::utils::patterns::GIT_CLONE_URL;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 use which::which;
 
@@ -15,10 +15,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     {
         false
     } else {
-        let re =
-            Regex::new(r"((git|ssh|http(s)?)|(git@[\w\.]+))(:(//)?)([\w\.@\:/\-~]+)(\.git)?(/)?")
-                .unwrap();
-        re.is_match(&command.script)
+        GIT_CLONE_URL.is_match(&command.script)
     }
 }
 
@@ -1,3 +1,4 @@
+use super::utils::patterns::{GIT_BAD_FLAG_AFTER_FILENAME, GIT_OPTION_AFTER_FILENAME};
 use crate::{
     cli::command::CrabCommand,
     rules::{
@@ -6,14 +7,10 @@ use crate::{
     },
     shell::Shell,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
-        let error_pattern = Regex::new(r"fatal: bad flag '(.*?)' used after filename").unwrap();
-        let error_pattern2 =
-            Regex::new(r"fatal: option '(.*?)' must come before non-option arguments").unwrap();
-        error_pattern.is_match(output) || error_pattern2.is_match(output)
+        GIT_BAD_FLAG_AFTER_FILENAME.is_match(output) || GIT_OPTION_AFTER_FILENAME.is_match(output)
     } else {
         false
     }
@@ -28,14 +25,11 @@ fn auxiliary_get_new_command(
     system_shell: Option<&dyn Shell>,
 ) -> Vec<String> {
     if let Some(output) = &command.output {
-        let error_pattern = Regex::new(r"fatal: bad flag '(.*?)' used after filename").unwrap();
-        let error_pattern2 =
-            Regex::new(r"fatal: option '(.*?)' must come before non-option arguments").unwrap();
         let mut command_parts = command.script_parts.clone();
 
-        if let Some(caps) = error_pattern
+        if let Some(caps) = GIT_BAD_FLAG_AFTER_FILENAME
             .captures(output)
-            .or_else(|| error_pattern2.captures(output))
+            .or_else(|| GIT_OPTION_AFTER_FILENAME.captures(output))
         {
             if let Some(bad_flag) = caps.get(1) {
                 let bad_flag = bad_flag.as_str();
@@ -1,4 +1,6 @@
-use regex::Regex;
+use super::utils::patterns::GIT_LFS_UNKNOWN_COMMAND;
+use crate::config::Settings;
+use std::sync::Arc;
 
 use crate::{
     cli::command::CrabCommand,
@@ -25,10 +27,10 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 fn auxiliary_get_new_command(
     command: &CrabCommand,
     system_shell: Option<&dyn Shell>,
+    settings: &Settings,
 ) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re = Regex::new(r#"Error: unknown command "([^"]*)" for "git-lfs""#).unwrap();
-        if let Some(caps) = re.captures(output) {
+        if let Some(caps) = GIT_LFS_UNKNOWN_COMMAND.captures(output) {
             if let Some(broken_cmd) = caps.get(1) {
                 let broken_cmd = broken_cmd.as_str();
                 let matched =
@@ -37,6 +39,7 @@ fn auxiliary_get_new_command(
                     command,
                     broken_cmd,
                     matched.iter().map(|s| s.as_str()).collect(),
+                    settings,
                 );
             }
         }
@@ -44,18 +47,26 @@ fn auxiliary_get_new_command(
     vec![]
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    get_new_command_with_git_support(auxiliary_get_new_command, command, system_shell)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    get_new_command_with_git_support(
+        |command, system_shell| auxiliary_get_new_command(command, system_shell, settings),
+        command,
+        system_shell,
+    )
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "git_lfs_mistype".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -64,6 +75,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{get_new_command, match_rule};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use crate::shell::Bash;
     use rstest::rstest;
 
@@ -87,6 +99,9 @@ mod tests {
     ) {
         let system_shell = Bash {};
         let mut command = CrabCommand::new(command.to_owned(), Some(stdout.to_owned()), None);
-        assert_eq!(get_new_command(&mut command, Some(&system_shell)), expected);
+        assert_eq!(
+            get_new_command(&mut command, Some(&system_shell), &Settings::default()),
+            expected
+        );
     }
 }
@@ -1,9 +1,9 @@
+use super::utils::patterns::{GIT_MERGE_REMOTE_BRANCH, GIT_MERGE_UNKNOWN_BRANCH};
 use super::{utils::git::get_new_command_with_git_support, Rule};
 use crate::utils::replace_argument;
 use crate::{
     cli::command::CrabCommand, rules::utils::git::match_rule_with_git_support, shell::Shell,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
@@ -24,10 +24,8 @@ fn auxiliary_get_new_command(
     system_shell: Option<&dyn Shell>,
 ) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let re_unknown_branch = Regex::new(r"merge: (.+) - not something we can merge").unwrap();
-        let re_remote_branch = Regex::new(r"Did you mean this\?\n\t([^\n]+)").unwrap();
-        if let Some(unknown_branch) = re_unknown_branch.captures(stdout) {
-            if let Some(remote_branch) = re_remote_branch.captures(stdout) {
+        if let Some(unknown_branch) = GIT_MERGE_UNKNOWN_BRANCH.captures(stdout) {
+            if let Some(remote_branch) = GIT_MERGE_REMOTE_BRANCH.captures(stdout) {
                 vec![replace_argument(
                     &command.script,
                     &unknown_branch[1],
@@ -1,12 +1,14 @@
+use crate::config::Settings;
 use crate::{
     cli::command::CrabCommand,
     rules::utils::git::match_rule_with_git_support,
     shell::Shell,
     utils::{get_all_matched_commands, replace_command},
 };
+use std::sync::Arc;
 
+use super::utils::patterns::GIT_NOT_COMMAND;
 use super::{utils::git::get_new_command_with_git_support, Rule};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
@@ -24,11 +26,10 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 fn auxiliary_get_new_command(
     command: &CrabCommand,
     system_shell: Option<&dyn Shell>,
+    settings: &Settings,
 ) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let re = Regex::new(r"git: '([^']*)' is not a git command").unwrap();
-
-        let broken_cmd = match re.captures(stdout) {
+        let broken_cmd = match GIT_NOT_COMMAND.captures(stdout) {
             Some(caps) => caps.get(1).map_or("", |m| m.as_str()),
             None => "",
         };
@@ -40,24 +41,33 @@ fn auxiliary_get_new_command(
             command,
             broken_cmd,
             matched.iter().map(|s| s.as_str()).collect(),
+            settings,
         )
     } else {
         Vec::<String>::new()
     }
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    get_new_command_with_git_support(auxiliary_get_new_command, command, system_shell)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    get_new_command_with_git_support(
+        |command, system_shell| auxiliary_get_new_command(command, system_shell, settings),
+        command,
+        system_shell,
+    )
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "git_not_command".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -66,6 +76,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{get_new_command, match_rule};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use rstest::rstest;
 
     const GIT_NOT_COMMAND: &str = r#"git: 'brnch' is not a git command. See 'git --help'.
@@ -111,6 +122,9 @@ The most similar commands are
         #[case] new_command: Vec<&str>,
     ) {
         let crab_command = &mut CrabCommand::new(script.to_owned(), Some(output), None);
-        assert_eq!(get_new_command(crab_command, None), new_command);
+        assert_eq!(
+            get_new_command(crab_command, None, &Settings::default()),
+            new_command
+        );
     }
 }
@@ -1,3 +1,4 @@
+use super::utils::patterns::GIT_PUSH_ARGUMENTS;
 use crate::{
     cli::command::CrabCommand,
     rules::utils::git::{get_new_command_with_git_support, match_rule_with_git_support},
@@ -5,7 +6,6 @@ use crate::{
     shell::Shell,
     utils::replace_argument,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
@@ -55,8 +55,7 @@ fn auxiliary_get_new_command(
             }
         }
 
-        let re = Regex::new(r"git push (.*)").unwrap();
-        let arguments = re.captures(stdout).unwrap()[1]
+        let arguments = GIT_PUSH_ARGUMENTS.captures(stdout).unwrap()[1]
             .replace('\'', r"\'")
             .trim()
             .to_string();
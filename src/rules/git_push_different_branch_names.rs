@@ -1,3 +1,4 @@
+use super::utils::patterns::GIT_PUSH_DIFFERENT_BRANCH_NAMES;
 use crate::{
     cli::command::CrabCommand,
     rules::{
@@ -6,7 +7,6 @@ use crate::{
     },
     shell::Shell,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
@@ -26,8 +26,7 @@ fn auxiliary_get_new_command(
     _system_shell: Option<&dyn Shell>,
 ) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let re = Regex::new(r"(?m)^ +(git push [^\s]+ [^\s]+)").unwrap();
-        let new_command = re
+        let new_command = GIT_PUSH_DIFFERENT_BRANCH_NAMES
             .captures_iter(stdout)
             .next()
             .map(|cap| cap[1].to_string());
@@ -1,3 +1,4 @@
+use super::utils::patterns::GIT_PUSH_WITHOUT_COMMITS;
 use crate::{
     cli::command::CrabCommand,
     rules::{
@@ -6,12 +7,10 @@ use crate::{
     },
     shell::Shell,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(stdout) = &command.output {
-        let re = Regex::new(r"src refspec \w+ does not match any").unwrap();
-        re.is_match(stdout)
+        GIT_PUSH_WITHOUT_COMMITS.is_match(stdout)
     } else {
         false
     }
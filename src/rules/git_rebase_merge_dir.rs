@@ -32,7 +32,7 @@ fn auxiliary_get_new_command(
                 "git rebase --skip",
                 rm_cmd_split[rm_cmd_split.len() - 4].trim(),
             ];
-            get_close_matches(&command.script, &command_list, Some(4), Some(0.))
+            get_close_matches(&command.script, &command_list, 4, 0.)
                 .iter()
                 .map(|s| s.to_string())
                 .collect()
@@ -50,7 +50,7 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
 
 pub fn get_rule() -> Rule {
     Rule::new(
-        "git_help_aliased".to_owned(),
+        "git_rebase_merge_dir".to_owned(),
         None,
         None,
         None,
@@ -1,3 +1,4 @@
+use super::utils::patterns::GIT_REMOTE_DELETE;
 use crate::{
     cli::command::CrabCommand,
     rules::{
@@ -6,7 +7,6 @@ use crate::{
     },
     shell::Shell,
 };
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     command.script.contains("remote delete")
@@ -20,8 +20,9 @@ fn auxiliary_get_new_command(
     command: &CrabCommand,
     system_shell: Option<&dyn Shell>,
 ) -> Vec<String> {
-    let re = Regex::new(r"delete").unwrap();
-    vec![re.replace(&command.script, "remove").to_string()]
+    vec![GIT_REMOTE_DELETE
+        .replace(&command.script, "remove")
+        .to_string()]
 }
 
 pub fn get_new_command(
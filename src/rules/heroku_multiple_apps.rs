@@ -1,6 +1,6 @@
+use super::utils::patterns::HEROKU_MULTIPLE_APPS;
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -16,13 +16,10 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re = Regex::new(r"([^ ]*) \([^)]*\)").unwrap();
-        let apps = re
+        let apps = HEROKU_MULTIPLE_APPS
             .captures_iter(output)
             .map(|cap| cap[1].to_owned())
             .collect::<Vec<_>>();
-        println!("{apps:?}");
-        println!("{output:?}");
         apps.iter()
             .map(|app| format!("{} --app {}", command.script, app))
             .collect()
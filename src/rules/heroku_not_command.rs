@@ -1,6 +1,6 @@
+use super::utils::patterns::HEROKU_NOT_COMMAND;
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -15,8 +15,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    let re = Regex::new(r"Run heroku _ to run ([^.]*)").unwrap();
-    let new_cmd = re
+    let new_cmd = HEROKU_NOT_COMMAND
         .captures(command.output.as_ref().unwrap())
         .unwrap()
         .get(1)
@@ -1,32 +1,32 @@
 use super::Rule;
 use crate::cli::command::CrabCommand;
+use crate::config::Settings;
 use crate::shell::Shell;
 use crate::utils::{get_close_matches, get_valid_history_without_current};
+use std::sync::Arc;
 
-pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
-    !get_close_matches(
-        &command.script,
-        get_valid_history_without_current(command, system_shell.unwrap())
-            .iter()
-            .map(|s| s.as_str())
-            .collect::<Vec<&str>>()
-            .as_slice(),
-        None,
-        None,
-    )
-    .is_empty()
+pub fn match_rule(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> bool {
+    !get_new_command(command, system_shell, settings).is_empty()
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
     get_close_matches(
         &command.script,
-        get_valid_history_without_current(command, system_shell.unwrap())
+        get_valid_history_without_current(command, system_shell.unwrap(), settings)
             .iter()
             .map(|s| s.as_str())
             .collect::<Vec<&str>>()
             .as_slice(),
-        None,
-        None,
+        settings.num_close_matches,
+        settings.close_match_cutoff,
     )
     .iter()
     .map(|&s| s.to_string())
@@ -34,13 +34,13 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "history".to_owned(),
         None,
         Some(9999),
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(match_rule),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -1,8 +1,10 @@
+use super::utils::patterns::HOSTSCLI_NO_SUCH_COMMAND;
 use super::{
     get_new_command_without_sudo, match_rule_without_sudo, utils::match_rule_with_is_app, Rule,
 };
+use crate::config::Settings;
 use crate::{cli::command::CrabCommand, shell::Shell, utils::replace_command};
-use regex::Regex;
+use std::sync::Arc;
 
 const NO_COMMAND: &str = "Error: No such command";
 const NO_WEBSITE: &str = "hostscli.errors.WebsiteImportError";
@@ -22,13 +24,12 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     )
 }
 
-pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
+pub fn auxiliary_get_new_command(command: &CrabCommand, settings: &Settings) -> Vec<String> {
     if let Some(output) = &command.output {
         if output.contains(NO_WEBSITE) {
             vec!["hostscli websites".to_owned()]
         } else {
-            let misspelled_command = Regex::new(r#"Error: No such command "(.*)""#)
-                .unwrap()
+            let misspelled_command = HOSTSCLI_NO_SUCH_COMMAND
                 .captures(output)
                 .and_then(|caps| caps.get(1).map(|m| m.as_str().to_owned()));
             match misspelled_command {
@@ -37,6 +38,7 @@ pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
                     command,
                     &misspelled_command,
                     vec!["block", "unblock", "websites", "block_all", "unblock_all"],
+                    settings,
                 ),
             }
         }
@@ -45,18 +47,25 @@ pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
     }
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    get_new_command_without_sudo(auxiliary_get_new_command, command)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    get_new_command_without_sudo(
+        |command| auxiliary_get_new_command(command, settings),
+        command,
+    )
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "hostscli".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -65,6 +74,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{get_new_command, match_rule};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use crate::shell::Bash;
     use rstest::rstest;
 
@@ -87,6 +97,9 @@ mod tests {
     ) {
         let system_shell = Bash {};
         let mut command = CrabCommand::new(command.to_owned(), Some(stdout.to_owned()), None);
-        assert_eq!(get_new_command(&mut command, None), expected);
+        assert_eq!(
+            get_new_command(&mut command, None, &Settings::default()),
+            expected
+        );
     }
 }
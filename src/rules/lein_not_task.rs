@@ -1,12 +1,14 @@
+use super::utils::patterns::LEIN_NOT_TASK;
 use super::{
     get_new_command_without_sudo, match_rule_without_sudo, utils::match_rule_with_is_app, Rule,
 };
+use crate::config::Settings;
 use crate::{
     cli::command::CrabCommand,
     shell::Shell,
     utils::{get_all_matched_commands, replace_command},
 };
-use regex::Regex;
+use std::sync::Arc;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -25,10 +27,9 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     )
 }
 
-pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
+pub fn auxiliary_get_new_command(command: &CrabCommand, settings: &Settings) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re = Regex::new(r"'([^']*)' is not a task").unwrap();
-        let broken_cmd = re
+        let broken_cmd = LEIN_NOT_TASK
             .captures(output)
             .unwrap()
             .get(1)
@@ -38,24 +39,32 @@ pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
             command,
             broken_cmd,
             new_cmds.iter().map(|s| s.as_ref()).collect(),
+            settings,
         )
     } else {
         Vec::<String>::new()
     }
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    get_new_command_without_sudo(auxiliary_get_new_command, command)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    get_new_command_without_sudo(
+        |command| auxiliary_get_new_command(command, settings),
+        command,
+    )
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "lein_not_task".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -64,6 +73,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{get_new_command, match_rule};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use crate::shell::Bash;
     use rstest::rstest;
 
@@ -88,6 +98,9 @@ mod tests {
     ) {
         let system_shell = Bash {};
         let mut command = CrabCommand::new(command.to_owned(), Some(stdout.to_owned()), None);
-        assert_eq!(get_new_command(&mut command, None), expected);
+        assert_eq!(
+            get_new_command(&mut command, None, &Settings::default()),
+            expected
+        );
     }
 }
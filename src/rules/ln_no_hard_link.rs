@@ -2,7 +2,7 @@ use crate::{cli::command::CrabCommand, shell::Shell};
 
 use super::{get_new_command_without_sudo, match_rule_without_sudo, Rule};
 
-use regex::Regex;
+use super::utils::patterns::LN_COMMAND;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -18,8 +18,9 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 pub fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
-    let re = Regex::new(r"^ln ").unwrap();
-    vec![re.replace_all(&command.script, "ln -s ").into_owned()]
+    vec![LN_COMMAND
+        .replace_all(&command.script, "ln -s ")
+        .into_owned()]
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
@@ -1,13 +1,10 @@
+use super::utils::patterns::LONG_FORM_HELP;
 use super::Rule;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
     if let Some(output) = &command.output {
-        let re =
-            Regex::new(r"(?:Run|Try) '([^']+)'(?: or '[^']+')? for (?:details|more information).")
-                .unwrap();
-        re.is_match(output) || output.contains("--help")
+        LONG_FORM_HELP.is_match(output) || output.contains("--help")
     } else {
         false
     }
@@ -15,10 +12,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let re =
-            Regex::new(r"(?:Run|Try) '([^']+)'(?: or '[^']+')? for (?:details|more information).")
-                .unwrap();
-        if let Some(caps) = re.captures(output) {
+        if let Some(caps) = LONG_FORM_HELP.captures(output) {
             vec![caps.get(1).map_or("", |m| m.as_str()).to_owned()]
         } else {
             vec![command.script.replace("-h", "--help")]
@@ -1,16 +1,14 @@
+use super::utils::patterns::{MERCURIAL_DID_YOU_MEAN, MERCURIAL_DID_YOU_MEAN_ONE_OF};
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell, utils::get_closest};
-use regex::Regex;
 
 fn extract_possibilities(command_output: &str) -> Vec<String> {
-    let re1 = Regex::new(r"\n\(did you mean one of ([^\?]+)\?\)").unwrap();
-    let re2 = Regex::new(r"\n    ([^$]+)$").unwrap();
-    if let Some(caps) = re1.captures(command_output) {
+    if let Some(caps) = MERCURIAL_DID_YOU_MEAN_ONE_OF.captures(command_output) {
         caps[1]
             .split(", ")
             .map(|s| s.to_string())
             .collect::<Vec<String>>()
-    } else if let Some(caps) = re2.captures(command_output) {
+    } else if let Some(caps) = MERCURIAL_DID_YOU_MEAN.captures(command_output) {
         caps[1]
             .split(' ')
             .map(|s| s.to_string())
@@ -1,5 +1,5 @@
+use super::utils::patterns::MKDIR_COMMAND;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 use super::{get_new_command_without_sudo, match_rule_without_sudo, Rule};
 
@@ -16,8 +16,9 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
-    let re = Regex::new(r"\bmkdir (.*)").unwrap();
-    vec![re.replace_all(&command.script, "mkdir -p $1").to_string()]
+    vec![MKDIR_COMMAND
+        .replace_all(&command.script, "mkdir -p $1")
+        .to_string()]
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
@@ -1,5 +1,17 @@
-use crate::shell::Shell;
+// The output of ohcrab is read by the shell alias, so the rules must not write to
+// stdout (see `logger` to debug them)
+#![deny(clippy::print_stdout)]
+
+use crate::config::Settings;
+use crate::logger;
+use crate::shell::{get_bash_type, Shell};
 use core::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::cli::{command::CorrectedCommand, command::CrabCommand};
 
@@ -125,9 +137,42 @@ mod sudo_command_from_user_path;
 mod tmux;
 mod unsudo;
 
+mod explain;
+mod plugin_rules;
+mod user_rules;
 mod utils;
 
+pub use explain::{explain_corrections, explain_rule, list_rules};
+pub use utils::messages::translate_to_english;
+pub use utils::patterns::ANSI_SEQUENCE;
+
 pub fn get_rules() -> Vec<Rule> {
+    let mut rules = get_builtin_rules();
+    // The unit tests must not depend on the rules installed on the machine
+    if cfg!(not(test)) {
+        add_external_rules(&mut rules, user_rules::get_user_rules());
+        add_external_rules(&mut rules, plugin_rules::get_plugin_rules());
+    }
+    rules
+}
+
+/// Adds the user rules or the plugins to `rules`, ignoring (with a warning) the ones
+/// named like a rule already there, so `enabled_rules`, `priority` and
+/// `rules explain` always refer to a single rule.
+fn add_external_rules(rules: &mut Vec<Rule>, external_rules: Vec<Rule>) {
+    for rule in external_rules {
+        if rules.iter().any(|known| known.name == rule.name) {
+            logger::warn(&format!(
+                "Ignoring rule {}: a rule with the same name already exists",
+                rule.name
+            ));
+        } else {
+            rules.push(rule);
+        }
+    }
+}
+
+fn get_builtin_rules() -> Vec<Rule> {
     vec![
         ag_literal::get_rule(),
         apt_get::get_rule(),
@@ -252,14 +297,23 @@ pub fn get_rules() -> Vec<Rule> {
         unsudo::get_rule(),
     ]
 }
+
+pub type MatchRuleFn =
+    Arc<dyn Fn(&mut CrabCommand, Option<&dyn Shell>, &Settings) -> bool + Send + Sync>;
+pub type GetNewCommandFn =
+    Arc<dyn Fn(&mut CrabCommand, Option<&dyn Shell>, &Settings) -> Vec<String> + Send + Sync>;
+pub type PriorityFn = Arc<dyn Fn(&CrabCommand, &Settings) -> Option<u16> + Send + Sync>;
+
+#[derive(Clone)]
 pub struct Rule {
     name: String,
     enabled_by_default: bool,
     priority: u16,
     requires_output: bool,
-    pub match_rule: fn(&mut CrabCommand, Option<&dyn Shell>) -> bool,
-    get_new_command: fn(&mut CrabCommand, Option<&dyn Shell>) -> Vec<String>,
+    pub match_rule: MatchRuleFn,
+    get_new_command: GetNewCommandFn,
     side_effect: Option<fn(CrabCommand, Option<&str>)>,
+    runtime_priority: Option<PriorityFn>,
 }
 
 impl fmt::Display for Rule {
@@ -277,6 +331,29 @@ impl Rule {
         match_rule: fn(&mut CrabCommand, Option<&dyn Shell>) -> bool,
         get_new_command: fn(&mut CrabCommand, Option<&dyn Shell>) -> Vec<String>,
         side_effect: Option<fn(CrabCommand, Option<&str>)>,
+    ) -> Self {
+        Self::from_closures(
+            name,
+            enabled_by_default,
+            priority,
+            requires_output,
+            Arc::new(move |command, system_shell, _| match_rule(command, system_shell)),
+            Arc::new(move |command, system_shell, _| get_new_command(command, system_shell)),
+            side_effect,
+        )
+    }
+
+    /// Creates a rule whose match and correction functions are closures receiving the
+    /// settings, for rules tuned by them (e.g. the number of close matches) and for
+    /// rules that are not compiled into ohcrab (e.g. user rules).
+    fn from_closures(
+        name: String,
+        enabled_by_default: Option<bool>,
+        priority: Option<u16>,
+        requires_output: Option<bool>,
+        match_rule: MatchRuleFn,
+        get_new_command: GetNewCommandFn,
+        side_effect: Option<fn(CrabCommand, Option<&str>)>,
     ) -> Self {
         Self {
             name,
@@ -286,35 +363,74 @@ impl Rule {
             match_rule,
             get_new_command,
             side_effect,
+            runtime_priority: None,
         }
     }
 
-    // Returns `True` if rule matches the command.
-    fn is_match(&self, mut command: CrabCommand, system_shell: &dyn Shell) -> bool {
+    /// Sets a function computing the priority of the rule for a given command, for
+    /// rules that only know their priority after running (e.g. plugins).
+    fn with_runtime_priority(mut self, runtime_priority: PriorityFn) -> Self {
+        self.runtime_priority = Some(runtime_priority);
+        self
+    }
+
+    /// Returns `true` if the rule should run with the given settings.
+    ///
+    /// Rules listed in `disabled_rules` never run, while rules that are disabled by
+    /// default only run when listed in `enabled_rules`.
+    fn is_enabled(&self, settings: &Settings) -> bool {
+        if settings.disabled_rules.contains(&self.name) {
+            false
+        } else {
+            self.enabled_by_default || settings.enabled_rules.contains(&self.name)
+        }
+    }
+
+    /// Returns the priority of the rule for the command. The priority in the user
+    /// settings takes precedence over the one computed at runtime, if any.
+    fn get_priority(&self, command: &CrabCommand, settings: &Settings) -> u16 {
+        match settings.priority.get(&self.name) {
+            Some(priority) => *priority,
+            None => self
+                .runtime_priority
+                .as_ref()
+                .and_then(|runtime_priority| runtime_priority(command, settings))
+                .unwrap_or(self.priority),
+        }
+    }
+
+    /// Returns `true` if the rule matches the command.
+    ///
+    /// Rules requiring the output of the command never match when the command was not
+    /// executed (script-only mode).
+    fn is_match(
+        &self,
+        command: &mut CrabCommand,
+        system_shell: &dyn Shell,
+        settings: &Settings,
+    ) -> bool {
         let script_only = command.output.is_none();
         if script_only && self.requires_output {
             return false;
         }
-        if (self.match_rule)(&mut command, Some(system_shell)) {
-            return true;
-        }
-        false
+        (self.match_rule)(command, Some(system_shell), settings)
     }
 
     fn get_corrected_commands(
         &self,
         command: &mut CrabCommand,
         system_shell: &dyn Shell,
+        settings: &Settings,
     ) -> Vec<CorrectedCommand> {
         let mut new_commands: Vec<CorrectedCommand> = vec![];
-        for (n, new_command) in (self.get_new_command)(command, Some(system_shell))
-            .iter()
-            .enumerate()
-        {
+        let candidates = (self.get_new_command)(command, Some(system_shell), settings);
+        let priority = self.get_priority(command, settings);
+        for (n, new_command) in candidates.iter().enumerate() {
             new_commands.push(CorrectedCommand::new(
                 new_command.to_owned(),
                 self.side_effect,
-                (n as u16 + 1) * self.priority,
+                (n as u16 + 1).saturating_mul(priority),
+                self.name.to_owned(),
             ));
         }
         new_commands
@@ -333,10 +449,13 @@ where
     }
 }
 
-pub fn get_new_command_without_sudo(
-    get_new_command_function: fn(&CrabCommand) -> Vec<String>,
+pub fn get_new_command_without_sudo<F>(
+    get_new_command_function: F,
     command: &mut CrabCommand,
-) -> Vec<String> {
+) -> Vec<String>
+where
+    F: Fn(&CrabCommand) -> Vec<String>,
+{
     if !command.script.starts_with("sudo ") {
         get_new_command_function(command)
     } else {
@@ -351,11 +470,13 @@ pub fn get_new_command_without_sudo(
 
 /// Generate a list of corrected commands for the given CrabCommand.
 ///
-/// This function takes a `CrabCommand` as input and iterates through the registered
-/// rules, applying each rule's match condition. The list of matching commands is then
+/// This function takes a `CrabCommand` as input and evaluates the enabled rules
+/// concurrently (see `evaluate_rules`). The list of matching commands is then
 /// reorganized and returned.
 ///
 /// * `command`: A `CrabCommand` for which to generate corrected commands.
+/// * `system_shell`: The shell used to call ohcrab.
+/// * `settings`: User settings selecting the rules and their priorities.
 ///
 /// # Returns
 ///
@@ -364,20 +485,589 @@ pub fn get_new_command_without_sudo(
 pub fn get_corrected_commands(
     command: &mut CrabCommand,
     system_shell: &dyn Shell,
+    settings: &Settings,
+) -> Vec<CorrectedCommand> {
+    let rules: Vec<Rule> = get_rules()
+        .into_iter()
+        .filter(|rule| rule.is_enabled(settings))
+        .collect();
+    let budget = Duration::from_millis(settings.rule_budget);
+    organize_commands(evaluate_rules(
+        rules,
+        command,
+        system_shell,
+        settings,
+        budget,
+    ))
+}
+
+const MIN_RULE_WORKERS: usize = 8;
+
+/// The result of running a rule on a command.
+enum RuleVerdict {
+    /// The rule matches and proposes these commands.
+    Matched(Vec<CorrectedCommand>),
+    NotMatched,
+    Panicked,
+    /// The rule did not finish before the end of the budget.
+    TimedOut,
+}
+
+/// Runs the rules on a small pool of threads and returns the commands they propose,
+/// in the order of the rules (see `run_rules`).
+fn evaluate_rules(
+    rules: Vec<Rule>,
+    command: &CrabCommand,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+    budget: Duration,
 ) -> Vec<CorrectedCommand> {
-    let mut corrected_commands: Vec<CorrectedCommand> = vec![];
-    for rule in get_rules() {
-        if (rule.match_rule)(command, Some(system_shell)) {
-            for corrected in rule.get_corrected_commands(command, system_shell) {
-                corrected_commands.push(corrected);
+    run_rules(rules, command, system_shell, settings, budget)
+        .into_iter()
+        .flat_map(|verdict| match verdict {
+            RuleVerdict::Matched(corrected_commands) => corrected_commands,
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Runs the rules on a small pool of threads and returns their verdicts, in the order
+/// of the rules.
+///
+/// Each rule works on its own copy of the command, so a rule modifying it (e.g. to
+/// remove `sudo`) does not change what the other rules see. When `budget` is over,
+/// the rules that did not finish time out and the workers stop taking new rules: a
+/// rule that is still running cannot be interrupted, so its thread ends with it (or
+/// with the process).
+fn run_rules(
+    rules: Vec<Rule>,
+    command: &CrabCommand,
+    system_shell: &dyn Shell,
+    settings: &Settings,
+    budget: Duration,
+) -> Vec<RuleVerdict> {
+    let deadline = Instant::now() + budget;
+    let num_rules = rules.len();
+    // Many rules wait for the filesystem or a subprocess rather than the CPU, so
+    // there are more workers than cores, and a slow rule does not block the others
+    let num_workers = thread::available_parallelism()
+        .map_or(MIN_RULE_WORKERS, |n| n.get().max(MIN_RULE_WORKERS))
+        .min(num_rules);
+
+    let rules = Arc::new(rules);
+    let command = Arc::new(command.clone());
+    // The threads may outlive this function, so they share an owned copy of the shell
+    let system_shell: Arc<dyn Shell> = get_bash_type(&system_shell.get_shell()).into();
+    let settings = Arc::new(settings.clone());
+    let next_rule = Arc::new(AtomicUsize::new(0));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    // The panics of the rules are reported with the rule name, the default hook
+    // would print them on the terminal
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(|info| logger::debug(&info.to_string())));
+    for _ in 0..num_workers {
+        let (rules, command, system_shell, settings, next_rule, cancelled, sender) = (
+            rules.clone(),
+            command.clone(),
+            system_shell.clone(),
+            settings.clone(),
+            next_rule.clone(),
+            cancelled.clone(),
+            sender.clone(),
+        );
+        thread::spawn(move || {
+            while !cancelled.load(Ordering::Relaxed) {
+                let index = next_rule.fetch_add(1, Ordering::Relaxed);
+                let Some(rule) = rules.get(index) else {
+                    break;
+                };
+                let mut command = (*command).clone();
+                let verdict = panic::catch_unwind(AssertUnwindSafe(|| {
+                    if logger::time(
+                        || format!("Rule {}", rule.name),
+                        || rule.is_match(&mut command, &*system_shell, &settings),
+                    ) {
+                        RuleVerdict::Matched(rule.get_corrected_commands(
+                            &mut command,
+                            &*system_shell,
+                            &settings,
+                        ))
+                    } else {
+                        RuleVerdict::NotMatched
+                    }
+                }))
+                .unwrap_or_else(|_| {
+                    logger::warn(&format!("Rule {} panicked", rule.name));
+                    RuleVerdict::Panicked
+                });
+                if sender.send((index, verdict)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(sender);
+
+    let mut results: Vec<Option<RuleVerdict>> = (0..num_rules).map(|_| None).collect();
+    let mut num_results = 0;
+    // Every rule reports a result, even when it panics
+    while num_results < num_rules {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((index, verdict)) => {
+                results[index] = Some(verdict);
+                num_results += 1;
+            }
+            Err(_) => {
+                cancelled.store(true, Ordering::Relaxed);
+                for (rule, result) in rules.iter().zip(&results) {
+                    if result.is_none() {
+                        logger::warn(&format!("Rule {} cancelled after {budget:?}", rule.name));
+                    }
+                }
+                break;
             }
         }
     }
-    organize_commands(corrected_commands)
+    panic::set_hook(previous_hook);
+    results
+        .into_iter()
+        .map(|verdict| verdict.unwrap_or(RuleVerdict::TimedOut))
+        .collect()
 }
 
+/// Returns `true` if at least one of the enabled rules needs the output of the command.
+///
+/// When no enabled rule needs it, the command does not have to be executed again
+/// and the correction can work on the script only.
+pub fn rules_require_output(settings: &Settings) -> bool {
+    get_rules()
+        .iter()
+        .any(|rule| rule.is_enabled(settings) && rule.requires_output)
+}
+
+/// Sorts the commands by priority and removes the duplicated scripts. The command
+/// kept for a script is the one with the best priority, and it lists the names of
+/// all the rules that proposed that script.
 pub fn organize_commands(mut corrected_commands: Vec<CorrectedCommand>) -> Vec<CorrectedCommand> {
-    corrected_commands.sort_by(|a, b| a.priority.cmp(&b.priority));
-    corrected_commands.dedup_by(|a, b| a.script.eq(&b.script));
-    corrected_commands
+    corrected_commands.sort_by_key(|cmd| cmd.priority);
+    let mut organized_commands: Vec<CorrectedCommand> = vec![];
+    for command in corrected_commands {
+        match organized_commands
+            .iter_mut()
+            .find(|kept| kept.script == command.script)
+        {
+            Some(kept) => {
+                for rule_name in command.rule_names {
+                    if !kept.rule_names.contains(&rule_name) {
+                        kept.rule_names.push(rule_name);
+                    }
+                }
+            }
+            None => organized_commands.push(command),
+        }
+    }
+    organized_commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_external_rules, evaluate_rules, get_builtin_rules, get_corrected_commands,
+        get_new_command_without_sudo, get_rules, organize_commands, rules_require_output, Rule,
+    };
+    use crate::cli::command::{CorrectedCommand, CrabCommand};
+    use crate::config::Settings;
+    use crate::shell::{Bash, Shell};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
+        true
+    }
+
+    fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
+        vec!["first".to_owned(), "second".to_owned()]
+    }
+
+    fn get_test_rule(enabled_by_default: bool) -> Rule {
+        get_test_rule_with_output(enabled_by_default, true)
+    }
+
+    fn get_test_rule_with_output(enabled_by_default: bool, requires_output: bool) -> Rule {
+        Rule::new(
+            "test_rule".to_owned(),
+            Some(enabled_by_default),
+            Some(100),
+            Some(requires_output),
+            match_rule,
+            get_new_command,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_rule_names_are_unique() {
+        let mut names: Vec<String> = get_rules().into_iter().map(|rule| rule.name).collect();
+        let num_rules = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), num_rules);
+    }
+
+    #[test]
+    fn test_add_external_rules() {
+        let mut rules = get_builtin_rules();
+        let num_rules = rules.len();
+        let mut sudo = get_test_rule(true);
+        sudo.name = "sudo".to_owned();
+
+        add_external_rules(
+            &mut rules,
+            vec![get_test_rule(true), sudo, get_test_rule(false)],
+        );
+        assert_eq!(rules.len(), num_rules + 1);
+        assert_eq!(rules[num_rules].name, "test_rule");
+        assert!(rules[num_rules].enabled_by_default);
+        assert_eq!(rules.iter().filter(|rule| rule.name == "sudo").count(), 1);
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let mut settings = Settings::default();
+        assert!(get_test_rule(true).is_enabled(&settings));
+        assert!(!get_test_rule(false).is_enabled(&settings));
+
+        settings.enabled_rules = vec!["test_rule".to_owned()];
+        assert!(get_test_rule(false).is_enabled(&settings));
+
+        settings.disabled_rules = vec!["test_rule".to_owned()];
+        assert!(!get_test_rule(true).is_enabled(&settings));
+        assert!(!get_test_rule(false).is_enabled(&settings));
+    }
+
+    #[test]
+    fn test_is_match_requires_output() {
+        let mut script_only = CrabCommand::new("test".to_owned(), None, None);
+        let mut with_output = CrabCommand::new("test".to_owned(), Some("".to_owned()), None);
+        let settings = Settings::default();
+
+        let rule = get_test_rule_with_output(true, true);
+        assert!(!rule.is_match(&mut script_only, &Bash {}, &settings));
+        assert!(rule.is_match(&mut with_output, &Bash {}, &settings));
+
+        let rule = get_test_rule_with_output(true, false);
+        assert!(rule.is_match(&mut script_only, &Bash {}, &settings));
+        assert!(rule.is_match(&mut with_output, &Bash {}, &settings));
+    }
+
+    #[test]
+    fn test_get_corrected_commands_skips_disabled_rules() {
+        // `git_push_force` is disabled by default
+        let output = "! [rejected]        master -> master (non-fast-forward)\n\
+            error: failed to push some refs to '/tmp/bar'\n\
+            hint: Updates were rejected because the tip of your current branch is behind\n";
+        let has_force_push = |settings: &Settings| {
+            let mut command =
+                CrabCommand::new("git push".to_owned(), Some(output.to_owned()), None);
+            get_corrected_commands(&mut command, &Bash {}, settings)
+                .iter()
+                .any(|cmd| cmd.script == "git push --force-with-lease")
+        };
+
+        let mut settings = Settings::default();
+        assert!(!has_force_push(&settings));
+        settings.enabled_rules = vec!["git_push_force".to_owned()];
+        assert!(has_force_push(&settings));
+    }
+
+    #[test]
+    fn test_rules_require_output() {
+        let mut settings = Settings::default();
+        assert!(rules_require_output(&settings));
+
+        // `git_hook_bypass` is the only rule working without output
+        settings.disabled_rules = get_rules()
+            .into_iter()
+            .map(|rule| rule.name)
+            .filter(|name| name != "git_hook_bypass")
+            .collect();
+        assert!(!rules_require_output(&settings));
+    }
+
+    #[test]
+    fn test_get_corrected_commands_priority() {
+        let mut command = CrabCommand::new("test".to_owned(), None, None);
+        let mut settings = Settings::default();
+        let rule = get_test_rule(true);
+
+        let priorities: Vec<u16> = rule
+            .get_corrected_commands(&mut command, &Bash {}, &settings)
+            .iter()
+            .map(|cmd| cmd.priority)
+            .collect();
+        assert_eq!(priorities, vec![100, 200]);
+
+        settings.priority.insert("test_rule".to_owned(), 7);
+        let priorities: Vec<u16> = rule
+            .get_corrected_commands(&mut command, &Bash {}, &settings)
+            .iter()
+            .map(|cmd| cmd.priority)
+            .collect();
+        assert_eq!(priorities, vec![7, 14]);
+    }
+
+    #[test]
+    fn test_organize_commands() {
+        let command = |script: &str, priority: u16, rule_name: &str| {
+            CorrectedCommand::new(script.to_owned(), None, priority, rule_name.to_owned())
+        };
+        let organized = organize_commands(vec![
+            command("git branch", 2000, "git_branch_typo"),
+            command("git status", 1500, "git_not_command"),
+            command("git branch", 1000, "git_not_command"),
+            command("git branch", 3000, "no_command"),
+            command("git branch", 4000, "git_not_command"),
+        ]);
+
+        let summary: Vec<(&str, u16, &str, Vec<String>)> = organized
+            .iter()
+            .map(|cmd| {
+                (
+                    cmd.script.as_str(),
+                    cmd.priority,
+                    cmd.rule_name.as_str(),
+                    cmd.rule_names.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "git branch",
+                    1000,
+                    "git_not_command",
+                    vec![
+                        "git_not_command".to_owned(),
+                        "git_branch_typo".to_owned(),
+                        "no_command".to_owned()
+                    ]
+                ),
+                (
+                    "git status",
+                    1500,
+                    "git_not_command",
+                    vec!["git_not_command".to_owned()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_rules_order() {
+        let rule = |name: &str, priority: u16, get_new_command| {
+            Rule::new(
+                name.to_owned(),
+                None,
+                Some(priority),
+                Some(false),
+                match_rule,
+                get_new_command,
+                None,
+            )
+        };
+        fn slow_get_new_command(
+            command: &mut CrabCommand,
+            system_shell: Option<&dyn Shell>,
+        ) -> Vec<String> {
+            thread::sleep(Duration::from_millis(50));
+            vec!["slow".to_owned()]
+        }
+        let rules = vec![
+            rule("slow", 100, slow_get_new_command),
+            rule("fast", 100, get_new_command),
+            rule("other", 50, get_new_command),
+        ];
+        let command = CrabCommand::new("test".to_owned(), None, None);
+
+        let scripts: Vec<(String, String)> = evaluate_rules(
+            rules,
+            &command,
+            &Bash {},
+            &Settings::default(),
+            Duration::from_secs(10),
+        )
+        .into_iter()
+        .map(|cmd| (cmd.rule_name, cmd.script))
+        .collect();
+        // Ordered like the rules, whatever the order they finished in
+        assert_eq!(
+            scripts,
+            vec![
+                ("slow".to_owned(), "slow".to_owned()),
+                ("fast".to_owned(), "first".to_owned()),
+                ("fast".to_owned(), "second".to_owned()),
+                ("other".to_owned(), "first".to_owned()),
+                ("other".to_owned(), "second".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_rules_budget() {
+        fn stuck_match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
+            thread::sleep(Duration::from_secs(10));
+            true
+        }
+        let rules = vec![
+            Rule::new(
+                "stuck".to_owned(),
+                None,
+                None,
+                Some(false),
+                stuck_match_rule,
+                get_new_command,
+                None,
+            ),
+            get_test_rule_with_output(true, false),
+        ];
+        let command = CrabCommand::new("test".to_owned(), None, None);
+
+        let start = Instant::now();
+        let scripts: Vec<String> = evaluate_rules(
+            rules,
+            &command,
+            &Bash {},
+            &Settings::default(),
+            Duration::from_millis(200),
+        )
+        .into_iter()
+        .map(|cmd| cmd.script)
+        .collect();
+        assert_eq!(scripts, vec!["first", "second"]);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_evaluate_rules_cancels_pending_rules() {
+        static NUM_STARTED: AtomicUsize = AtomicUsize::new(0);
+        fn slow_match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
+            NUM_STARTED.fetch_add(1, Ordering::Relaxed);
+            thread::sleep(Duration::from_millis(100));
+            true
+        }
+        let num_rules = 200;
+        let rules = (0..num_rules)
+            .map(|i| {
+                Rule::new(
+                    format!("slow_{i}"),
+                    None,
+                    None,
+                    Some(false),
+                    slow_match_rule,
+                    get_new_command,
+                    None,
+                )
+            })
+            .collect();
+        let command = CrabCommand::new("test".to_owned(), None, None);
+
+        evaluate_rules(
+            rules,
+            &command,
+            &Bash {},
+            &Settings::default(),
+            Duration::from_millis(150),
+        );
+        // The workers finish their current rule, then stop
+        thread::sleep(Duration::from_millis(500));
+        let num_started = NUM_STARTED.load(Ordering::Relaxed);
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(NUM_STARTED.load(Ordering::Relaxed), num_started);
+        assert!(num_started < num_rules);
+    }
+
+    #[test]
+    fn test_evaluate_rules_panic() {
+        fn panicking_match_rule(
+            command: &mut CrabCommand,
+            system_shell: Option<&dyn Shell>,
+        ) -> bool {
+            panic!("broken rule")
+        }
+        let rules = vec![
+            Rule::new(
+                "broken".to_owned(),
+                None,
+                None,
+                Some(false),
+                panicking_match_rule,
+                get_new_command,
+                None,
+            ),
+            get_test_rule_with_output(true, false),
+        ];
+        let command = CrabCommand::new("test".to_owned(), None, None);
+
+        let start = Instant::now();
+        let scripts: Vec<String> = evaluate_rules(
+            rules,
+            &command,
+            &Bash {},
+            &Settings::default(),
+            Duration::from_secs(10),
+        )
+        .into_iter()
+        .map(|cmd| cmd.script)
+        .collect();
+        assert_eq!(scripts, vec!["first", "second"]);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_evaluate_rules_isolates_commands() {
+        // Removes `sudo` from the script of the command it is given
+        fn unsudo_get_new_command(
+            command: &mut CrabCommand,
+            system_shell: Option<&dyn Shell>,
+        ) -> Vec<String> {
+            get_new_command_without_sudo(|command| vec![command.script.to_owned()], command)
+        }
+        fn script_get_new_command(
+            command: &mut CrabCommand,
+            system_shell: Option<&dyn Shell>,
+        ) -> Vec<String> {
+            vec![command.script.to_owned() + " again"]
+        }
+        let rule = |name: &str, get_new_command| {
+            Rule::new(
+                name.to_owned(),
+                None,
+                None,
+                Some(false),
+                match_rule,
+                get_new_command,
+                None,
+            )
+        };
+        let rules = vec![
+            rule("unsudo", unsudo_get_new_command),
+            rule("script", script_get_new_command),
+        ];
+        let command = CrabCommand::new("sudo ls".to_owned(), None, None);
+
+        let scripts: Vec<String> = evaluate_rules(
+            rules,
+            &command,
+            &Bash {},
+            &Settings::default(),
+            Duration::from_secs(10),
+        )
+        .into_iter()
+        .map(|cmd| cmd.script)
+        .collect();
+        assert_eq!(scripts, vec!["sudo ls", "sudo ls again"]);
+    }
 }
@@ -1,19 +1,23 @@
+use super::utils::patterns::{MVN_AVAILABLE_LIFECYCLE_PHASES, MVN_UNKNOWN_LIFECYCLE_PHASE};
 use super::{utils::match_rule_with_is_app, Rule};
+use crate::config::Settings;
 use crate::{
     cli::command::CrabCommand,
     shell::Shell,
     utils::{get_close_matches, replace_command},
 };
-use regex::Regex;
+use std::sync::Arc;
 
 fn get_failed_lifecycle(command_output: &str) -> Option<String> {
-    let re = Regex::new(r#"\[ERROR\] Unknown lifecycle phase \"(.+)\""#).unwrap();
-    re.captures(command_output).map(|caps| caps[1].to_string())
+    MVN_UNKNOWN_LIFECYCLE_PHASE
+        .captures(command_output)
+        .map(|caps| caps[1].to_string())
 }
 
 fn get_available_lifecycles(command_output: &str) -> Option<String> {
-    let re = Regex::new(r"Available lifecycle phases are: (.+) -> \[Help 1\]").unwrap();
-    re.captures(command_output).map(|caps| caps[1].to_string())
+    MVN_AVAILABLE_LIFECYCLE_PHASES
+        .captures(command_output)
+        .map(|caps| caps[1].to_string())
 }
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
@@ -28,7 +32,11 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     match_rule_with_is_app(auxiliary_match_rule, command, vec!["mvn"], None)
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
     if let Some(output) = &command.output {
         let failed_lifecycle = get_failed_lifecycle(output);
         let available_lifecycles = get_available_lifecycles(output);
@@ -36,9 +44,13 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
             (failed_lifecycle, available_lifecycles)
         {
             let available_lifecycles: Vec<&str> = available_lifecycles.split(", ").collect();
-            let selected_lifecycle =
-                get_close_matches(&failed_lifecycle, &available_lifecycles, None, None);
-            replace_command(command, &failed_lifecycle, selected_lifecycle)
+            let selected_lifecycle = get_close_matches(
+                &failed_lifecycle,
+                &available_lifecycles,
+                settings.num_close_matches,
+                settings.close_match_cutoff,
+            );
+            replace_command(command, &failed_lifecycle, selected_lifecycle, settings)
         } else {
             vec![]
         }
@@ -48,13 +60,13 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "mvn_unknown_lifecycle_phase".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -63,6 +75,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{get_new_command, match_rule};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use rstest::rstest;
 
     const ERROR_UNKNOWN_LIFECYCLE: &str = "[ERROR] Unknown lifecycle phase \"cle\". You must specify a valid lifecycle phase or a goal in the format <plugin-prefix>:<goal> or <plugin-group-id>:<plugin-artifact-id>[:<plugin-version>]:<goal>. Available lifecycle phases are: validate, initialize, generate-sources, process-sources, generate-resources, process-resources, compile, process-classes, generate-test-sources, process-test-sources, generate-test-resources, process-test-resources, test-compile, process-test-classes, test, prepare-package, package, pre-integration-test, integration-test, post-integration-test, verify, install, deploy, pre-clean, clean, post-clean, pre-site, site, post-site, site-deploy. -> [Help 1]";
@@ -99,6 +112,9 @@ mod tests {
         #[case] expected: Vec<&str>,
     ) {
         let mut command = CrabCommand::new(command.to_owned(), Some(stdout.to_owned()), None);
-        assert_eq!(get_new_command(&mut command, None), expected);
+        assert_eq!(
+            get_new_command(&mut command, None, &Settings::default()),
+            expected
+        );
     }
 }
@@ -1,10 +1,11 @@
+use super::utils::patterns::NIXOS_NIX_ENV_INSTALL;
 use super::Rule;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn get_name(command_output: &str) -> Option<String> {
-    let re = Regex::new(r"nix-env -iA ([^\s]*)").unwrap();
-    re.captures(command_output).map(|caps| caps[1].to_string())
+    NIXOS_NIX_ENV_INSTALL
+        .captures(command_output)
+        .map(|caps| caps[1].to_string())
 }
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
@@ -1,33 +1,58 @@
+use crate::config::Settings;
 use crate::{
-    cli::command::CrabCommand,
+    cli::command::{CrabCommand, EXIT_CODE_NOT_FOUND},
     shell::Shell,
     utils::{get_all_executable, get_close_matches},
 };
+use std::sync::Arc;
 use which::which;
 
 use super::Rule;
 
-pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
-    which(&command.script_parts[0]).is_err()
-        & (if let Some(output) = &command.output {
-            output.contains("not found") | output.contains("is not recognized as")
-        } else {
-            false
-        })
-        & !get_close_matches(
-            &command.script_parts[0],
-            get_all_executable()
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<&str>>()
-                .as_slice(),
-            None,
-            None,
-        )
-        .is_empty()
+/// Returns `false` when the exit code shows the command was found. Only the POSIX
+/// shells (and fish) are known to exit with `EXIT_CODE_NOT_FOUND` otherwise.
+fn may_be_not_found(command: &CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
+    match (
+        command.exit_code,
+        system_shell.map(|shell| shell.get_shell()),
+    ) {
+        (Some(exit_code), Some(shell)) if ["bash", "zsh", "fish"].contains(&shell.as_str()) => {
+            exit_code == EXIT_CODE_NOT_FOUND
+        }
+        _ => true,
+    }
+}
+
+pub fn match_rule(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> bool {
+    may_be_not_found(command, system_shell)
+        && which(&command.script_parts[0]).is_err()
+            & (if let Some(output) = &command.output {
+                output.contains("not found") | output.contains("is not recognized as")
+            } else {
+                false
+            })
+            & !get_close_matches(
+                &command.script_parts[0],
+                get_all_executable(system_shell)
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<&str>>()
+                    .as_slice(),
+                settings.num_close_matches,
+                settings.close_match_cutoff,
+            )
+            .is_empty()
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
     let old_command = &command.script_parts[0];
     let old_parameters = {
         if command.script_parts.len() > 1 {
@@ -39,12 +64,17 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
 
     // TODO: Check shell history
     let mut new_cmds: Vec<&str> = vec![];
-    let executables = get_all_executable();
+    let executables = get_all_executable(system_shell);
     let str_executables = executables
         .iter()
         .map(|s| s.as_str())
         .collect::<Vec<&str>>();
-    for cmd in get_close_matches(old_command, &str_executables, None, None) {
+    for cmd in get_close_matches(
+        old_command,
+        &str_executables,
+        settings.num_close_matches,
+        settings.close_match_cutoff,
+    ) {
         if !new_cmds.contains(&cmd) {
             new_cmds.push(cmd);
         }
@@ -56,13 +86,34 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "no_command".to_owned(),
         None,
         Some(3000),
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(match_rule),
+        Arc::new(get_new_command),
         None,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::may_be_not_found;
+    use crate::cli::command::CrabCommand;
+    use crate::shell::{Bash, PowerShell};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(None, true)]
+    #[case(Some(127), true)]
+    #[case(Some(1), false)]
+    fn test_may_be_not_found(#[case] exit_code: Option<i32>, #[case] expected: bool) {
+        let command =
+            CrabCommand::new("gti status".to_owned(), None, None).with_exit_status(exit_code, None);
+        assert_eq!(may_be_not_found(&command, Some(&Bash {})), expected);
+        // Other shells use other exit codes
+        assert!(may_be_not_found(&command, Some(&PowerShell {})));
+        assert!(may_be_not_found(&command, None));
+    }
+}
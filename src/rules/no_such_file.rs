@@ -1,18 +1,19 @@
+use super::utils::patterns::{
+    CP_NOT_A_DIRECTORY, CP_NO_SUCH_FILE, MV_NOT_A_DIRECTORY, MV_NO_SUCH_FILE,
+};
 use super::{
     get_new_command_without_sudo, match_rule_without_sudo, utils::match_rule_with_is_app, Rule,
 };
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 fn get_file(command_output: &str) -> Option<String> {
     let patterns = [
-        r"mv: cannot move '[^']*' to '([^']*)': No such file or directory",
-        r"mv: cannot move '[^']*' to '([^']*)': Not a directory",
-        r"cp: cannot create regular file '([^']*)': No such file or directory",
-        r"cp: cannot create regular file '([^']*)': Not a directory",
+        &MV_NO_SUCH_FILE,
+        &MV_NOT_A_DIRECTORY,
+        &CP_NO_SUCH_FILE,
+        &CP_NOT_A_DIRECTORY,
     ];
-    for pattern in patterns.iter() {
-        let re = Regex::new(pattern).unwrap();
+    for re in patterns {
         if let Some(caps) = re.captures(command_output) {
             return caps.get(1).map(|m| m.as_str().to_owned());
         }
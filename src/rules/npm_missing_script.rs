@@ -1,14 +1,16 @@
+use super::utils::patterns::NPM_MISSING_SCRIPT;
 use super::{
     utils::{match_rule_with_is_app, npm::is_npm_available},
     Rule,
 };
+use crate::config::Settings;
 use crate::{
     cli::command::CrabCommand,
     rules::utils::npm::{mockable_get_scripts, run_npm_command},
     shell::Shell,
     utils::replace_command,
 };
-use regex::Regex;
+use std::sync::Arc;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -26,14 +28,16 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     match_rule_with_is_app(auxiliary_match_rule, command, vec!["npm"], None)
 }
 
-pub fn mockable_get_new_command<F>(command: &mut CrabCommand, fn_get_scripts: F) -> Vec<String>
+pub fn mockable_get_new_command<F>(
+    command: &mut CrabCommand,
+    fn_get_scripts: F,
+    settings: &Settings,
+) -> Vec<String>
 where
     F: Fn() -> Vec<u8>,
 {
     if let Some(output) = &command.output {
-        let re = Regex::new(r".*missing script: (.*)\n").unwrap();
-        if let Some(caps) = re.captures(output) {
-            println!("{caps:?}");
+        if let Some(caps) = NPM_MISSING_SCRIPT.captures(output) {
             replace_command(
                 command,
                 &caps[1],
@@ -41,6 +45,7 @@ where
                     .iter()
                     .map(|s| s.as_str())
                     .collect(),
+                settings,
             )
         } else {
             vec![]
@@ -50,18 +55,22 @@ where
     }
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    mockable_get_new_command(command, run_npm_command)
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
+    mockable_get_new_command(command, run_npm_command, settings)
 }
 
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "npm_missing_script".to_owned(),
         Some(is_npm_available()),
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -70,6 +79,7 @@ pub fn get_rule() -> Rule {
 mod tests {
     use super::{match_rule, mockable_get_new_command};
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
     use crate::shell::Bash;
     use rstest::rstest;
 
@@ -129,7 +139,11 @@ available via `npm run-script`:
         let system_shell = Bash {};
         let mut command = CrabCommand::new(command.to_owned(), Some(stdout.to_owned()), None);
         assert_eq!(
-            mockable_get_new_command(&mut command, || run_script_stdout.to_vec()),
+            mockable_get_new_command(
+                &mut command,
+                || run_script_stdout.to_vec(),
+                &Settings::default()
+            ),
             expected
         );
     }
@@ -12,21 +12,6 @@ where
     F: Fn() -> Vec<u8>,
 {
     if let Some(output) = &command.output {
-        println!("{:?}", output.contains("Usage: npm <command>"));
-        println!(
-            "{:?}",
-            !command
-                .script_parts
-                .iter()
-                .any(|part| part.starts_with("ru"))
-        );
-        println!("{:?}", mockable_get_scripts(&fn_run_npm_command));
-        println!("{:?}", &command.script_parts[1]);
-        println!(
-            "{:?}",
-            mockable_get_scripts(&fn_run_npm_command).contains(&command.script_parts[1])
-        );
-
         output.contains("Usage: npm <command>")
             && !command
                 .script_parts
@@ -1,6 +1,6 @@
+use super::utils::patterns::{PIP_MAYBE_YOU_MEANT, PIP_UNKNOWN_COMMAND};
 use super::{match_rule_without_sudo, utils::match_rule_with_is_app, Rule};
 use crate::{cli::command::CrabCommand, shell::Shell, utils::replace_argument};
-use regex::Regex;
 
 fn auxiliary_match_rule(command: &CrabCommand) -> bool {
     if let Some(output) = &command.output {
@@ -28,14 +28,11 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        println!("{output:?}");
-        let broken_cmd = Regex::new(r#"ERROR: unknown command "([^"]+)""#)
-            .unwrap()
+        let broken_cmd = PIP_UNKNOWN_COMMAND
             .captures(output)
             .and_then(|caps| caps.get(1).map(|m| m.as_str().to_owned()))
             .unwrap_or_default();
-        let new_cmd = Regex::new(r#"maybe you meant "([^"]+)""#)
-            .unwrap()
+        let new_cmd = PIP_MAYBE_YOU_MEANT
             .captures(output)
             .and_then(|caps| caps.get(1).map(|m| m.as_str().to_owned()))
             .unwrap_or_default();
@@ -0,0 +1,382 @@
+use is_executable::IsExecutable;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+
+use super::Rule;
+use crate::cli::command::{kill_process_group, set_process_group, wait_or_kill, CrabCommand};
+use crate::config::{get_config_dir, Settings};
+use crate::executables::get_path_executables;
+use crate::logger;
+use crate::shell::Shell;
+
+const PLUGIN_PREFIX: &str = "ohcrab-rule-";
+
+static PLUGIN_RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+
+/// Message sent to a plugin on its stdin.
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    script: &'a str,
+    script_parts: &'a [String],
+    output: Option<&'a str>,
+    stdout: Option<&'a str>,
+    stderr: Option<&'a str>,
+    exit_code: Option<i32>,
+    shell: Option<String>,
+}
+
+/// Message read from the stdout of a plugin.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct PluginResponse {
+    #[serde(rename = "match")]
+    is_match: bool,
+    #[serde(default)]
+    commands: Vec<String>,
+    priority: Option<u16>,
+}
+
+/// Last answer of a plugin, with the script and output it was given.
+type ResponseCache = Arc<Mutex<Option<((String, Option<String>), PluginResponse)>>>;
+
+/// Returns the rules implemented by external `ohcrab-rule-*` executables, discovering
+/// them the first time this function is called.
+pub fn get_plugin_rules() -> Vec<Rule> {
+    PLUGIN_RULES
+        .get_or_init(|| {
+            discover_plugins(&get_config_dir().join("plugins"), get_path_executables())
+                .into_iter()
+                .map(|(name, path)| get_plugin_rule(name, path))
+                .collect()
+        })
+        .clone()
+}
+
+/// Finds the `ohcrab-rule-<name>` executables in `plugin_dir`, then among the
+/// executables of the PATH (taken from the cached index, so the PATH directories are
+/// not listed on every run). When two plugins have the same name, the one in
+/// `plugin_dir` wins.
+///
+/// The plugins of the PATH are returned by name, to be looked up in the PATH when
+/// they run.
+fn discover_plugins(plugin_dir: &Path, path_executables: &[String]) -> Vec<(String, PathBuf)> {
+    let mut plugins: Vec<(String, PathBuf)> = match fs::read_dir(plugin_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_executable())
+            .filter_map(|path| {
+                let file_name = path.file_name()?.to_str()?;
+                let name = file_name.strip_prefix(PLUGIN_PREFIX)?.to_owned();
+                Some((name, path))
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    plugins.sort();
+
+    let mut path_plugins: Vec<(String, PathBuf)> = path_executables
+        .iter()
+        .filter_map(|file_name| {
+            let name = file_name.strip_prefix(PLUGIN_PREFIX)?;
+            if plugins.iter().any(|(known, _)| known == name) {
+                return None;
+            }
+            Some((name.to_owned(), PathBuf::from(file_name)))
+        })
+        .collect();
+    path_plugins.sort();
+    plugins.extend(path_plugins);
+    plugins
+}
+
+/// Builds the rule calling the plugin at `path`. The plugin runs once per command:
+/// its answer is shared by the match, the correction and the priority of the rule.
+fn get_plugin_rule(name: String, path: PathBuf) -> Rule {
+    let cache: ResponseCache = Arc::new(Mutex::new(None));
+
+    let get_response =
+        move |command: &CrabCommand, system_shell: Option<&dyn Shell>, settings: &Settings| {
+            let key = (command.script.to_owned(), command.output.to_owned());
+            let mut cached = cache.lock().unwrap();
+            match cached.as_ref() {
+                Some((cached_key, response)) if *cached_key == key => Some(response.clone()),
+                _ => {
+                    let timeout = Duration::from_millis(settings.plugin_timeout);
+                    let response = run_plugin(&path, command, system_shell, timeout)?;
+                    *cached = Some((key, response.clone()));
+                    Some(response)
+                }
+            }
+        };
+    let get_response = Arc::new(get_response);
+
+    let match_response = get_response.clone();
+    let match_rule = move |command: &mut CrabCommand,
+                           system_shell: Option<&dyn Shell>,
+                           settings: &Settings| {
+        match_response(command, system_shell, settings).is_some_and(|response| response.is_match)
+    };
+    let commands_response = get_response.clone();
+    let get_new_command =
+        move |command: &mut CrabCommand, system_shell: Option<&dyn Shell>, settings: &Settings| {
+            commands_response(command, system_shell, settings)
+                .map(|response| response.commands)
+                .unwrap_or_default()
+        };
+    let runtime_priority = move |command: &CrabCommand, settings: &Settings| {
+        get_response(command, None, settings).and_then(|r| r.priority)
+    };
+
+    Rule::from_closures(
+        name,
+        None,
+        None,
+        None,
+        Arc::new(match_rule),
+        Arc::new(get_new_command),
+        None,
+    )
+    .with_runtime_priority(Arc::new(runtime_priority))
+}
+
+/// Sends the command to the plugin and parses its answer. Returns `None` if the plugin
+/// fails, answers with invalid JSON or does not answer within `timeout`.
+fn run_plugin(
+    path: &Path,
+    command: &CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    timeout: Duration,
+) -> Option<PluginResponse> {
+    let request = serde_json::to_string(&PluginRequest {
+        script: &command.script,
+        script_parts: &command.script_parts,
+        output: command.output.as_deref(),
+        stdout: command.stdout.as_deref(),
+        stderr: command.stderr.as_deref(),
+        exit_code: command.exit_code,
+        shell: system_shell.map(|shell| shell.get_shell()),
+    })
+    .ok()?;
+
+    let deadline = Instant::now() + timeout;
+    let mut command = Command::new(path);
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    set_process_group(&mut command);
+    let mut child = command.spawn().ok()?;
+
+    // Writing and reading happen in separate threads, so a plugin that never reads
+    // its input or never closes its output cannot block the correction
+    let mut stdin = child.stdin.take()?;
+    thread::spawn(move || {
+        let _ = stdin.write_all(request.as_bytes());
+    });
+    let mut stdout = child.stdout.take()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut answer = String::new();
+        let _ = stdout.read_to_string(&mut answer);
+        let _ = sender.send(answer);
+    });
+
+    let answer = receiver.recv_timeout(timeout);
+    // A plugin closing its stdout may keep running, it is killed with the rest of the
+    // timeout. The processes it started are killed too, even once it has exited (it
+    // is not waited for yet, so its process group still exists).
+    wait_or_kill(&mut child, deadline);
+    kill_process_group(&mut child);
+    let _ = child.wait();
+    match answer {
+        Ok(answer) => serde_json::from_str(&answer)
+            .map_err(|err| logger::warn(&format!("Invalid answer from {}: {err}", path.display())))
+            .ok(),
+        Err(_) => {
+            logger::warn(&format!("Plugin {} timed out", path.display()));
+            None
+        }
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::{discover_plugins, get_plugin_rule, run_plugin, PluginResponse};
+    use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
+    use crate::shell::Bash;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const PLUGIN: &str = r#"#!/bin/sh
+input=$(cat)
+case "$input" in
+    *'"script":"mycli deplyo"'*'"shell":"bash"'*)
+        echo '{"match": true, "commands": ["mycli deploy", "mycli deploy --force"], "priority": 50}' ;;
+    *)
+        echo '{"match": false}' ;;
+esac
+"#;
+
+    fn write_plugin(dir: &Path, file_name: &str, content: &str) -> PathBuf {
+        let path = dir.join(file_name);
+        fs::write(&path, content).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_run_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(dir.path(), "ohcrab-rule-mycli", PLUGIN);
+        let command = CrabCommand::new("mycli deplyo".to_owned(), Some("".to_owned()), None);
+
+        assert_eq!(
+            run_plugin(&path, &command, Some(&Bash {}), Duration::from_secs(5)),
+            Some(PluginResponse {
+                is_match: true,
+                commands: vec!["mycli deploy".to_owned(), "mycli deploy --force".to_owned()],
+                priority: Some(50),
+            })
+        );
+        assert_eq!(
+            run_plugin(&path, &command, None, Duration::from_secs(5)),
+            Some(PluginResponse {
+                is_match: false,
+                commands: vec![],
+                priority: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_plugin_invalid_answer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(dir.path(), "ohcrab-rule-broken", "#!/bin/sh\necho oops\n");
+        let command = CrabCommand::new("mycli".to_owned(), None, None);
+        assert_eq!(
+            run_plugin(&path, &command, None, Duration::from_secs(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_run_plugin_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(dir.path(), "ohcrab-rule-slow", "#!/bin/sh\nexec sleep 10\n");
+        let command = CrabCommand::new("mycli".to_owned(), None, None);
+
+        let start = Instant::now();
+        assert_eq!(
+            run_plugin(&path, &command, None, Duration::from_millis(200)),
+            None
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_run_plugin_keeps_running() {
+        // Answers, then keeps running with its output closed
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(
+            dir.path(),
+            "ohcrab-rule-lingering",
+            "#!/bin/sh\necho '{\"match\": false}'\nexec sleep 10 >&-\n",
+        );
+        let command = CrabCommand::new("mycli".to_owned(), None, None);
+
+        let start = Instant::now();
+        assert_eq!(
+            run_plugin(&path, &command, None, Duration::from_millis(200)),
+            Some(PluginResponse {
+                is_match: false,
+                commands: vec![],
+                priority: None,
+            })
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_run_plugin_kills_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let path = write_plugin(
+            dir.path(),
+            "ohcrab-rule-parent",
+            &format!("#!/bin/sh\nsleep 10 &\necho $! > {}\n", pid_file.display()),
+        );
+        let command = CrabCommand::new("mycli".to_owned(), None, None);
+
+        assert_eq!(
+            run_plugin(&path, &command, None, Duration::from_millis(200)),
+            None
+        );
+        thread::sleep(Duration::from_millis(100));
+        let pid = fs::read_to_string(&pid_file).unwrap();
+        // Gone, or a zombie waiting to be reaped
+        let state = fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        assert!(state.is_empty() || state.contains(") Z "), "{state}");
+    }
+
+    #[test]
+    fn test_plugin_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(dir.path(), "ohcrab-rule-mycli", PLUGIN);
+        let rule = get_plugin_rule("mycli".to_owned(), path);
+        let mut command = CrabCommand::new("mycli deplyo".to_owned(), Some("".to_owned()), None);
+
+        assert!((rule.match_rule)(
+            &mut command,
+            Some(&Bash {}),
+            &Settings::default()
+        ));
+        assert_eq!(
+            (rule.get_new_command)(&mut command, Some(&Bash {}), &Settings::default()),
+            vec!["mycli deploy", "mycli deploy --force"]
+        );
+        assert_eq!(
+            rule.runtime_priority.as_ref().unwrap()(&command, &Settings::default()),
+            Some(50)
+        );
+
+        let mut command = CrabCommand::new("mycli deploy".to_owned(), Some("".to_owned()), None);
+        assert!(!(rule.match_rule)(
+            &mut command,
+            Some(&Bash {}),
+            &Settings::default()
+        ));
+    }
+
+    #[test]
+    fn test_discover_plugins() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let mycli = write_plugin(plugin_dir.path(), "ohcrab-rule-mycli", PLUGIN);
+        write_plugin(plugin_dir.path(), "not-a-plugin", PLUGIN);
+        fs::write(plugin_dir.path().join("ohcrab-rule-not-executable"), PLUGIN).unwrap();
+        let path_executables = [
+            "ohcrab-rule-other".to_owned(),
+            "ohcrab-rule-mycli".to_owned(),
+            "git".to_owned(),
+            "ohcrab-rule-another".to_owned(),
+        ];
+
+        assert_eq!(
+            discover_plugins(plugin_dir.path(), &path_executables),
+            vec![
+                ("mycli".to_owned(), mycli),
+                ("another".to_owned(), PathBuf::from("ohcrab-rule-another")),
+                ("other".to_owned(), PathBuf::from("ohcrab-rule-other")),
+            ]
+        );
+        assert!(discover_plugins(&plugin_dir.path().join("missing"), &[]).is_empty());
+    }
+}
@@ -1,8 +1,6 @@
+use super::utils::patterns::PYTHON_MISSING_MODULE;
 use super::Rule;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
-
-const MISSING_MODULE: &str = r"ModuleNotFoundError: No module named '([^']+)'";
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
     if let Some(output) = &command.output {
@@ -14,8 +12,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let missing_module = Regex::new(MISSING_MODULE)
-            .unwrap()
+        let missing_module = PYTHON_MISSING_MODULE
             .captures(output)
             .and_then(|caps| caps.get(1).map(|m| m.as_str().to_owned()))
             .unwrap_or("".to_owned());
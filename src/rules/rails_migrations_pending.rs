@@ -1,8 +1,6 @@
+use super::utils::patterns::RAILS_MIGRATIONS_SUGGESTION;
 use super::Rule;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
-
-const SUGGESTION_REGEX: &str = r"To resolve this issue, run:\s+(.*?)\n";
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
     if let Some(output) = &command.output {
@@ -14,7 +12,7 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
     if let Some(output) = &command.output {
-        let migration_script = match Regex::new(SUGGESTION_REGEX).unwrap().captures(output) {
+        let migration_script = match RAILS_MIGRATIONS_SUGGESTION.captures(output) {
             Some(caps) => caps.get(1).map_or("", |m| m.as_str()),
             None => "",
         };
@@ -1,11 +1,10 @@
+use super::utils::patterns::SHELL_PROMPT_LITERAL;
 use super::Rule;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
     if let Some(output) = &command.output {
-        let re = Regex::new(r"^[\s]*\$ [\S]+").unwrap();
-        output.contains("$: command not found") && re.is_match(&command.script)
+        output.contains("$: command not found") && SHELL_PROMPT_LITERAL.is_match(&command.script)
     } else {
         false
     }
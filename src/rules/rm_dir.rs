@@ -1,5 +1,5 @@
+use super::utils::patterns::RM_COMMAND;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 
 use super::{get_new_command_without_sudo, match_rule_without_sudo, Rule};
 
@@ -16,7 +16,6 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
 }
 
 fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
-    let re = Regex::new(r"\brm (.*)").unwrap();
     let repl = |caps: &regex::Captures| {
         let arguments = if command.script.contains("hdfs") {
             "-r"
@@ -25,7 +24,7 @@ fn auxiliary_get_new_command(command: &CrabCommand) -> Vec<String> {
         };
         format!("rm {} {}", arguments, &caps[1])
     };
-    vec![re.replace(&command.script, repl).to_string()]
+    vec![RM_COMMAND.replace(&command.script, repl).to_string()]
 }
 
 pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
@@ -1,5 +1,8 @@
 use super::Rule;
-use crate::{cli::command::CrabCommand, shell::Shell};
+use crate::{
+    cli::command::{CrabCommand, EXIT_CODE_NOT_FOUND},
+    shell::Shell,
+};
 
 const PATTERNS: [&str; 28] = [
     "permission denied",
@@ -33,6 +36,11 @@ const PATTERNS: [&str; 28] = [
 ];
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
+    // The shell could not find the command, running it as root changes nothing. A
+    // command it could not execute (126) may be in a directory only root can read.
+    if command.exit_code == Some(EXIT_CODE_NOT_FOUND) {
+        return false;
+    }
     if let Some(output) = &command.output {
         if !command.script_parts.is_empty()
             && !command.script_parts.contains(&"&&".to_owned())
@@ -118,6 +126,21 @@ mod tests {
         assert_eq!(match_rule(&mut command, None), is_match);
     }
 
+    #[rstest]
+    #[case(None, true)]
+    #[case(Some(1), true)]
+    #[case(Some(126), true)]
+    #[case(Some(127), false)]
+    fn test_match_exit_code(#[case] exit_code: Option<i32>, #[case] is_match: bool) {
+        let mut command = CrabCommand::new(
+            "./run.sh".to_owned(),
+            Some("Permission denied".to_owned()),
+            None,
+        )
+        .with_exit_status(exit_code, None);
+        assert_eq!(match_rule(&mut command, None), is_match);
+    }
+
     #[rstest]
     #[case("ls", "Permission denied", vec!["sudo ls"])]
     #[case("echo a > b", "Permission denied", vec!["sudo sh -c \"echo a > b\""])]
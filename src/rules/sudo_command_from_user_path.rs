@@ -1,7 +1,7 @@
+use super::utils::patterns::SUDO_COMMAND_NOT_FOUND;
 use super::{utils::match_rule_with_is_app, Rule};
 use crate::utils::replace_argument;
 use crate::{cli::command::CrabCommand, shell::Shell};
-use regex::Regex;
 use which::which;
 
 fn is_terminal_command(terminal_command: &str) -> bool {
@@ -9,8 +9,9 @@ fn is_terminal_command(terminal_command: &str) -> bool {
 }
 
 fn get_command_name(command_output: &str) -> Option<String> {
-    let re = Regex::new(r"sudo: (.*): command not found").unwrap();
-    re.captures(command_output).map(|caps| caps[1].to_owned())
+    SUDO_COMMAND_NOT_FOUND
+        .captures(command_output)
+        .map(|caps| caps[1].to_owned())
 }
 
 fn auxiliary_match_rule<F>(command: &CrabCommand, fn_is_terminal_command: F) -> bool
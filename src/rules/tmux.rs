@@ -1,6 +1,8 @@
+use super::utils::patterns::TMUX_AMBIGUOUS_COMMAND;
 use super::Rule;
+use crate::config::Settings;
 use crate::{cli::command::CrabCommand, shell::Shell, utils::replace_command};
-use regex::Regex;
+use std::sync::Arc;
 
 pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> bool {
     if let Some(output) = &command.output {
@@ -12,10 +14,13 @@ pub fn match_rule(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -
     false
 }
 
-pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shell>) -> Vec<String> {
-    let re = Regex::new(r"ambiguous command: (.*), could be: (.*)").unwrap();
+pub fn get_new_command(
+    command: &mut CrabCommand,
+    system_shell: Option<&dyn Shell>,
+    settings: &Settings,
+) -> Vec<String> {
     if let Some(stdout) = &command.output {
-        let caps = re.captures(stdout).unwrap();
+        let caps = TMUX_AMBIGUOUS_COMMAND.captures(stdout).unwrap();
 
         let old_cmd = caps.get(1).unwrap().as_str();
         let suggestions: Vec<&str> = caps
@@ -26,19 +31,19 @@ pub fn get_new_command(command: &mut CrabCommand, system_shell: Option<&dyn Shel
             .map(|s| s.trim())
             .collect();
 
-        replace_command(command, old_cmd, suggestions)
+        replace_command(command, old_cmd, suggestions, settings)
     } else {
         Vec::<String>::new()
     }
 }
 pub fn get_rule() -> Rule {
-    Rule::new(
+    Rule::from_closures(
         "tmux".to_owned(),
         None,
         None,
         None,
-        match_rule,
-        get_new_command,
+        Arc::new(|command, system_shell, _| match_rule(command, system_shell)),
+        Arc::new(get_new_command),
         None,
     )
 }
@@ -46,6 +51,7 @@ pub fn get_rule() -> Rule {
 #[cfg(test)]
 mod tests {
     use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
 
     use super::{get_new_command, match_rule};
 
@@ -59,7 +65,7 @@ mod tests {
             None,
         );
         assert_eq!(
-            get_new_command(&mut CrabCommand::new("tmux list".to_owned(), Some("ambiguous command: list, could be: list-buffers, list-clients, list-commands, list-keys, list-panes, list-sessions, list-windows".to_owned()), None), None),
+            get_new_command(&mut CrabCommand::new("tmux list".to_owned(), Some("ambiguous command: list, could be: list-buffers, list-clients, list-commands, list-keys, list-panes, list-sessions, list-windows".to_owned()), None), None, &Settings::default()),
             vec!["tmux list-keys", "tmux list-panes", "tmux list-buffers"]
         );
     }
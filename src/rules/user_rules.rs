@@ -0,0 +1,422 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use super::utils::match_rule_with_is_app;
+use super::{get_new_command_without_sudo, match_rule_without_sudo, Rule};
+use crate::cli::command::{shlex_split, CrabCommand, Stream};
+use crate::config::{get_config_dir, Settings};
+use crate::logger;
+use crate::shell::Shell;
+
+static USER_RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserRuleSpec {
+    name: Option<String>,
+    apps: Vec<String>,
+    at_least: Option<usize>,
+    #[serde(default)]
+    patterns: Vec<String>,
+    /// Stream the patterns are matched against, the whole output if absent.
+    stream: Option<Stream>,
+    replacement: String,
+    priority: Option<u16>,
+    enabled_by_default: Option<bool>,
+    #[serde(default)]
+    sudo_support: bool,
+}
+
+/// Part of a replacement template.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// `{script}`: the whole script.
+    Script,
+    /// `{script_parts[N]}`: the N-th part of the script.
+    Part(usize),
+    /// `{script_parts[N:]}`: the parts of the script from the N-th on, joined by spaces.
+    PartsFrom(usize),
+    /// `{N}` or `{name}`: a capture group of the pattern matching the output.
+    Group(String),
+}
+
+/// Returns the rules declared by the user in `~/.config/ohcrab/rules/*.toml`, loading
+/// them the first time this function is called.
+pub fn get_user_rules() -> Vec<Rule> {
+    USER_RULES
+        .get_or_init(|| load_user_rules(&get_config_dir().join("rules")))
+        .clone()
+}
+
+/// Loads the rules from every `.toml` file in `rules_dir`, sorted by file name.
+///
+/// Invalid files are skipped with a warning.
+fn load_user_rules(rules_dir: &Path) -> Vec<Rule> {
+    let mut paths: Vec<_> = match fs::read_dir(rules_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect(),
+        Err(_) => return vec![],
+    };
+    paths.sort();
+
+    let mut rules = vec![];
+    for path in paths {
+        let default_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_owned();
+        let rule = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| parse_user_rule(&content, &default_name));
+        match rule {
+            Ok(rule) => rules.push(rule),
+            Err(err) => logger::warn(&format!("Ignoring user rule {}: {err}", path.display())),
+        }
+    }
+    rules
+}
+
+/// Builds a `Rule` from the content of a rule file.
+fn parse_user_rule(content: &str, default_name: &str) -> Result<Rule, String> {
+    let spec: UserRuleSpec = toml::from_str(content).map_err(|err| err.to_string())?;
+    if spec.apps.is_empty() {
+        return Err("`apps` must not be empty".to_owned());
+    }
+    let patterns = spec
+        .patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(|err| err.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let template = parse_template(&spec.replacement)?;
+    let requires_output = !patterns.is_empty();
+
+    let apps = spec.apps;
+    let at_least = spec.at_least;
+    let stream = spec.stream;
+    let sudo_support = spec.sudo_support;
+    let match_patterns = patterns.clone();
+    let match_rule = move |command: &mut CrabCommand, _: Option<&dyn Shell>, _: &Settings| {
+        let auxiliary_match_rule = |command: &CrabCommand| {
+            match_rule_with_is_app(
+                |command| {
+                    match_patterns.is_empty()
+                        || get_matched_output(command, stream).is_some_and(|output| {
+                            match_patterns.iter().any(|re| re.is_match(output))
+                        })
+                },
+                command,
+                apps.iter().map(|app| app.as_str()).collect(),
+                at_least,
+            )
+        };
+        if sudo_support {
+            match_rule_without_sudo(auxiliary_match_rule, command)
+        } else {
+            auxiliary_match_rule(command)
+        }
+    };
+
+    let get_new_command = move |command: &mut CrabCommand, _: Option<&dyn Shell>, _: &Settings| {
+        let auxiliary_get_new_command =
+            |command: &CrabCommand| render_template(&template, &patterns, stream, command);
+        if sudo_support {
+            get_new_command_without_sudo(auxiliary_get_new_command, command)
+        } else {
+            auxiliary_get_new_command(command)
+        }
+    };
+
+    Ok(Rule::from_closures(
+        spec.name.unwrap_or(default_name.to_owned()),
+        spec.enabled_by_default,
+        spec.priority,
+        Some(requires_output),
+        Arc::new(match_rule),
+        Arc::new(get_new_command),
+        None,
+    ))
+}
+
+/// Splits a replacement template into segments. `{{` and `}}` are literal braces.
+fn parse_template(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(format!("unterminated `{{` in replacement `{template}`"))
+                        }
+                    }
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(parse_placeholder(&placeholder)?);
+            }
+            '}' => return Err(format!("unmatched `}}` in replacement `{template}`")),
+            _ => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Segment, String> {
+    let invalid = || format!("invalid placeholder `{{{placeholder}}}`");
+    if placeholder == "script" {
+        Ok(Segment::Script)
+    } else if let Some(index) = placeholder
+        .strip_prefix("script_parts[")
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        match index.strip_suffix(':') {
+            Some(start) => start.parse().map(Segment::PartsFrom),
+            None => index.parse().map(Segment::Part),
+        }
+        .map_err(|_| invalid())
+    } else if !placeholder.is_empty()
+        && placeholder
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        Ok(Segment::Group(placeholder.to_owned()))
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Returns the output the patterns are matched against: the given stream, or the
+/// whole output.
+///
+/// The streams are only known when ohcrab runs the command itself. The output read
+/// from the session log in instant mode, or given to `rules explain --output`, is
+/// all on stdout (and there is no stderr), so the whole output is used instead.
+fn get_matched_output(command: &CrabCommand, stream: Option<Stream>) -> Option<&String> {
+    match stream {
+        Some(stream) if command.stderr.is_some() => command.get_stream(stream),
+        _ => command.output.as_ref(),
+    }
+}
+
+/// Renders the template for the given command. Returns no command if the template
+/// refers to a part of the script that does not exist.
+fn render_template(
+    template: &[Segment],
+    patterns: &[Regex],
+    stream: Option<Stream>,
+    command: &CrabCommand,
+) -> Vec<String> {
+    // `command.script` may have been stripped of `sudo`, so the parts are computed again
+    let script_parts = shlex_split(&command.script);
+    let captures = get_matched_output(command, stream)
+        .and_then(|output| patterns.iter().find_map(|re| re.captures(output)));
+
+    let mut new_command = String::new();
+    for segment in template {
+        match segment {
+            Segment::Literal(literal) => new_command.push_str(literal),
+            Segment::Script => new_command.push_str(&command.script),
+            Segment::Part(index) => match script_parts.get(*index) {
+                Some(part) => new_command.push_str(part),
+                None => return vec![],
+            },
+            Segment::PartsFrom(start) => {
+                new_command.push_str(&script_parts.get(*start..).unwrap_or_default().join(" "))
+            }
+            Segment::Group(group) => {
+                let value = captures.as_ref().and_then(|captures| match group.parse() {
+                    Ok(index) => captures.get(index),
+                    Err(_) => captures.name(group),
+                });
+                new_command.push_str(value.map_or("", |m| m.as_str()));
+            }
+        }
+    }
+    vec![new_command.trim().to_owned()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_user_rules, parse_template, parse_user_rule, Segment};
+    use crate::cli::command::CrabCommand;
+    use crate::config::Settings;
+    use crate::shell::Bash;
+    use rstest::rstest;
+    use std::fs;
+
+    const RULE: &str = r#"
+        apps = ["mycli"]
+        patterns = ["unknown command '(?P<wrong>[^']+)', did you mean '([^']+)'"]
+        replacement = "{script_parts[0]} {2} {script_parts[2:]}"
+        priority = 900
+        sudo_support = true
+    "#;
+    const OUTPUT: &str = "error: unknown command 'deplyo', did you mean 'deploy'?";
+
+    #[rstest]
+    #[case("mycli deplyo --env prod", OUTPUT, true)]
+    #[case("sudo mycli deplyo", OUTPUT, true)]
+    #[case("mycli deploy", "done", false)]
+    #[case("othercli deplyo", OUTPUT, false)]
+    fn test_match(#[case] command: &str, #[case] output: &str, #[case] is_match: bool) {
+        let rule = parse_user_rule(RULE, "mycli").unwrap();
+        let mut command = CrabCommand::new(command.to_owned(), Some(output.to_owned()), None);
+        assert_eq!(
+            (rule.match_rule)(&mut command, Some(&Bash {}), &Settings::default()),
+            is_match
+        );
+    }
+
+    #[rstest]
+    #[case("mycli deplyo --env prod", vec!["mycli deploy --env prod"])]
+    #[case("mycli deplyo", vec!["mycli deploy"])]
+    #[case("sudo mycli deplyo", vec!["sudo mycli deploy"])]
+    fn test_get_new_command(#[case] command: &str, #[case] expected: Vec<&str>) {
+        let rule = parse_user_rule(RULE, "mycli").unwrap();
+        let mut command = CrabCommand::new(command.to_owned(), Some(OUTPUT.to_owned()), None);
+        assert_eq!(
+            (rule.get_new_command)(&mut command, Some(&Bash {}), &Settings::default()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_match_stream() {
+        let rule = parse_user_rule(
+            r#"
+            apps = ["mycli"]
+            patterns = ["did you mean '([^']+)'"]
+            stream = "stderr"
+            replacement = "{script_parts[0]} {1}"
+            "#,
+            "mycli",
+        )
+        .unwrap();
+        let hint = "did you mean 'deploy'".to_owned();
+
+        let mut command = CrabCommand::new("mycli deplyo".to_owned(), None, Some(hint.clone()));
+        assert!((rule.match_rule)(
+            &mut command,
+            Some(&Bash {}),
+            &Settings::default()
+        ));
+        assert_eq!(
+            (rule.get_new_command)(&mut command, Some(&Bash {}), &Settings::default()),
+            vec!["mycli deploy"]
+        );
+
+        // The same text on stdout does not match
+        let mut command = CrabCommand::new(
+            "mycli deplyo".to_owned(),
+            Some(hint.clone()),
+            Some("".to_owned()),
+        );
+        assert!(!(rule.match_rule)(
+            &mut command,
+            Some(&Bash {}),
+            &Settings::default()
+        ));
+
+        // Unless the streams are unknown (e.g. in instant mode)
+        let mut command = CrabCommand::new("mycli deplyo".to_owned(), Some(hint), None);
+        assert!((rule.match_rule)(
+            &mut command,
+            Some(&Bash {}),
+            &Settings::default()
+        ));
+    }
+
+    #[test]
+    fn test_parse_user_rule_attributes() {
+        let rule = parse_user_rule(RULE, "mycli").unwrap();
+        assert_eq!(rule.name, "mycli");
+        assert_eq!(rule.priority, 900);
+        assert!(rule.requires_output);
+        assert!(rule.enabled_by_default);
+
+        let rule = parse_user_rule(
+            "name = \"mycli_help\"\napps = [\"mycli\"]\nreplacement = \"{script} --help\"",
+            "mycli",
+        )
+        .unwrap();
+        assert_eq!(rule.name, "mycli_help");
+        assert_eq!(rule.priority, 1000);
+        assert!(!rule.requires_output);
+    }
+
+    #[rstest]
+    #[case("apps = []\nreplacement = \"x\"")]
+    #[case("apps = [\"mycli\"]")]
+    #[case("apps = [\"mycli\"]\nreplacement = \"{script_parts[x]}\"")]
+    #[case("apps = [\"mycli\"]\nreplacement = \"git {script\"")]
+    #[case("apps = [\"mycli\"]\nreplacement = \"git {script\"")]
+    #[case("apps = [\"mycli\"]\npatterns = [\"(\"]\nreplacement = \"x\"")]
+    #[case("apps = [\"mycli\"]\nreplacement = \"x\"\nunknown = 1")]
+    #[case("apps = [\"mycli\"]\nreplacement = \"x\"\nstream = \"stdin\"")]
+    fn test_parse_user_rule_invalid(#[case] content: &str) {
+        assert!(parse_user_rule(content, "invalid").is_err());
+    }
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(
+            parse_template("{{{script}}} {script_parts[1]} {script_parts[2:]} {name}").unwrap(),
+            vec![
+                Segment::Literal("{".to_owned()),
+                Segment::Script,
+                Segment::Literal("} ".to_owned()),
+                Segment::Part(1),
+                Segment::Literal(" ".to_owned()),
+                Segment::PartsFrom(2),
+                Segment::Literal(" ".to_owned()),
+                Segment::Group("name".to_owned()),
+            ]
+        );
+        assert!(parse_template("{}").is_err());
+        assert!(parse_template("a } b").is_err());
+    }
+
+    #[test]
+    fn test_load_user_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b_rule.toml"), RULE).unwrap();
+        fs::write(
+            dir.path().join("a_rule.toml"),
+            "apps = [\"mycli\"]\nreplacement = \"{script} --help\"",
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.toml"), "apps = ").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a rule").unwrap();
+
+        let names: Vec<String> = load_user_rules(dir.path())
+            .into_iter()
+            .map(|rule| rule.name)
+            .collect();
+        assert_eq!(names, vec!["a_rule", "b_rule"]);
+    }
+}
@@ -1,3 +1,4 @@
+use super::patterns::GIT_ALIAS_EXPANSION;
 use crate::{cli::command::CrabCommand, shell::Shell};
 use regex::Regex;
 use shlex::split as shlex_split;
@@ -29,8 +30,7 @@ where
     // perform git aliases expansion
     if let Some(stdout) = &command.output {
         if stdout.contains("trace: alias expansion:") {
-            let re = Regex::new(r"trace: alias expansion: ([^ ]*) => ([^\n]*)").unwrap();
-            if let Some(search) = re.captures(stdout) {
+            if let Some(search) = GIT_ALIAS_EXPANSION.captures(stdout) {
                 let alias = search.get(1).map_or("", |m| m.as_str());
 
                 // by default git quotes everything, for example:
@@ -73,8 +73,7 @@ where
     // perform git aliases expansion
     if let Some(stdout) = &new_command.output {
         if stdout.contains("trace: alias expansion:") {
-            let re = Regex::new(r"trace: alias expansion: ([^ ]*) => ([^\n]*)").unwrap();
-            if let Some(search) = re.captures(stdout) {
+            if let Some(search) = GIT_ALIAS_EXPANSION.captures(stdout) {
                 let shlex_quoter = Quoter::new();
                 let alias = search.get(1).map_or("", |m| m.as_str());
 
@@ -90,7 +89,7 @@ where
                     .collect::<Vec<_>>()
                     .join(" ");
 
-                let re = Regex::new(&format!(r"\b{alias}\b")).unwrap();
+                let re = Regex::new(&format!(r"\b{}\b", regex::escape(alias))).unwrap();
                 let new_script = re.replace(&new_command.script, &expansion);
 
                 *new_command = new_command.update(Some(new_script.to_string()), None, None);
@@ -0,0 +1,193 @@
+use super::patterns::{
+    LazyRegex, GIT_NOT_COMMAND_DE, GIT_NOT_COMMAND_ES, GIT_NOT_COMMAND_FR, GIT_NOT_COMMAND_PT,
+};
+
+/// Common error messages (from the C library, coreutils, bash and git) with their
+/// German, French, Spanish and Portuguese translations.
+const MESSAGES: &[(&str, &[&str])] = &[
+    (
+        "No such file or directory",
+        &[
+            "Datei oder Verzeichnis nicht gefunden",
+            "Aucun fichier ou dossier de ce type",
+            "No existe el archivo o el directorio",
+            "Arquivo ou diretório inexistente",
+            "Arquivo ou diretório não encontrado",
+        ],
+    ),
+    (
+        "Permission denied",
+        &[
+            "Keine Berechtigung",
+            "Permission non accordée",
+            "Permiso denegado",
+            "Permissão negada",
+        ],
+    ),
+    (
+        "Operation not permitted",
+        &[
+            "Die Operation ist nicht erlaubt",
+            "Opération non permise",
+            "Operación no permitida",
+            "Operação não permitida",
+        ],
+    ),
+    (
+        "Not a directory",
+        &[
+            "Ist kein Verzeichnis",
+            "N'est pas un dossier",
+            "No es un directorio",
+            "Não é um diretório",
+        ],
+    ),
+    (
+        "Is a directory",
+        &[
+            "Ist ein Verzeichnis",
+            "Est un dossier",
+            "Es un directorio",
+            "É um diretório",
+        ],
+    ),
+    (
+        "Directory not empty",
+        &[
+            "Verzeichnis nicht leer",
+            "Le dossier n'est pas vide",
+            "El directorio no está vacío",
+            "Diretório não vazio",
+        ],
+    ),
+    (
+        "File exists",
+        &[
+            "Die Datei existiert bereits",
+            "Le fichier existe",
+            "El archivo ya existe",
+            "Arquivo existe",
+        ],
+    ),
+    (
+        "command not found",
+        &[
+            "Befehl nicht gefunden",
+            "commande introuvable",
+            "orden no encontrada",
+            "comando não encontrado",
+        ],
+    ),
+    (
+        "The most similar commands are",
+        &[
+            "Die ähnlichsten Befehle sind",
+            "Les commandes les plus ressemblantes sont",
+            "Los comandos más similares son",
+            "Os comandos mais semelhantes são",
+        ],
+    ),
+    (
+        "The most similar command is",
+        &[
+            "Der ähnlichste Befehl ist",
+            "La commande la plus ressemblante est",
+            "El comando más similar es",
+            "O comando mais semelhante é",
+        ],
+    ),
+];
+
+/// Translated messages holding a value, with the English replacement.
+const LOCALIZED_PATTERNS: &[(&LazyRegex, &str)] = &[
+    (&GIT_NOT_COMMAND_DE, GIT_NOT_COMMAND_EN),
+    (&GIT_NOT_COMMAND_FR, GIT_NOT_COMMAND_EN),
+    (&GIT_NOT_COMMAND_ES, GIT_NOT_COMMAND_EN),
+    (&GIT_NOT_COMMAND_PT, GIT_NOT_COMMAND_EN),
+];
+
+const GIT_NOT_COMMAND_EN: &str = "git: '$1' is not a git command. See 'git --help'.";
+
+/// Replaces the known translated messages of `output` by their English version, so
+/// the rules also match the output of commands run with another language.
+///
+/// A message is only translated when it is a whole line, or ends a line after `: `
+/// (like in `ls: cannot access 'foo': No such file or directory`), so the same words
+/// elsewhere (e.g. in a file name) are left as they are.
+pub fn translate_to_english(output: &str) -> String {
+    let mut output: String = output.split_inclusive('\n').map(translate_line).collect();
+    for (pattern, english) in LOCALIZED_PATTERNS {
+        if pattern.is_match(&output) {
+            output = pattern.replace_all(&output, *english).into_owned();
+        }
+    }
+    output
+}
+
+fn translate_line(line: &str) -> String {
+    let content = line.trim_end_matches(['\r', '\n']);
+    let line_end = &line[content.len()..];
+    for (english, translations) in MESSAGES {
+        for translation in translations.iter() {
+            if content == *translation {
+                return format!("{english}{line_end}");
+            }
+            if let Some(prefix) = content
+                .strip_suffix(translation)
+                .filter(|prefix| prefix.ends_with(": "))
+            {
+                return format!("{prefix}{english}{line_end}");
+            }
+        }
+    }
+    line.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::translate_to_english;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        "ls: Zugriff auf 'foo' nicht möglich: Datei oder Verzeichnis nicht gefunden",
+        "ls: Zugriff auf 'foo' nicht möglich: No such file or directory"
+    )]
+    #[case(
+        "mkdir: impossible de créer le répertoire « a/b »: Aucun fichier ou dossier de ce type",
+        "mkdir: impossible de créer le répertoire « a/b »: No such file or directory"
+    )]
+    #[case("bash: gti: orden no encontrada", "bash: gti: command not found")]
+    #[case(
+        "rm: não foi possível remover 'foo': É um diretório",
+        "rm: não foi possível remover 'foo': Is a directory"
+    )]
+    #[case(
+        "git: 'brnch' ist kein Git-Befehl. Siehe 'git --help'.\n\nDer ähnlichste Befehl ist\n\tbranch",
+        "git: 'brnch' is not a git command. See 'git --help'.\n\nThe most similar command is\n\tbranch"
+    )]
+    #[case(
+        "git : 'stats' n'est pas une commande git. Voir 'git --help'.\n\nLes commandes les plus ressemblantes sont\n\tstatus\n\tstash",
+        "git: 'stats' is not a git command. See 'git --help'.\n\nThe most similar commands are\n\tstatus\n\tstash"
+    )]
+    #[case(
+        "ls: cannot access 'foo': No such file or directory",
+        "ls: cannot access 'foo': No such file or directory"
+    )]
+    #[case(
+        "Datei oder Verzeichnis nicht gefunden\r\n",
+        "No such file or directory\r\n"
+    )]
+    #[case(
+        "cat: 'Keine Berechtigung.txt': Datei oder Verzeichnis nicht gefunden",
+        "cat: 'Keine Berechtigung.txt': No such file or directory"
+    )]
+    #[case("echo Est un dossier", "echo Est un dossier")]
+    #[case(
+        "mygit: 'brnch' ist kein Git-Befehl. Siehe 'git --help'.",
+        "mygit: 'brnch' ist kein Git-Befehl. Siehe 'git --help'."
+    )]
+    fn test_translate_to_english(#[case] output: &str, #[case] expected: &str) {
+        assert_eq!(translate_to_english(output), expected);
+    }
+}
@@ -2,8 +2,10 @@ use crate::cli::command::CrabCommand;
 use std::path::Path;
 
 pub mod git;
+pub mod messages;
 pub mod npm;
 pub mod parameterized_tests;
+pub mod patterns;
 
 /// Matches a rule with a given command if it is an application.
 ///
@@ -1,4 +1,4 @@
-use regex::Regex;
+use super::patterns::NPM_SCRIPT_LINE;
 use std::process::Command;
 use which::which;
 
@@ -22,7 +22,6 @@ where
     let stdout = String::from_utf8_lossy(&npm_output);
     let mut should_yield = false;
     let mut scripts = Vec::new();
-    let re = Regex::new(r"^  \S+").unwrap();
 
     for line in stdout.lines() {
         if line.contains("available via `npm run-script`:") {
@@ -30,7 +29,7 @@ where
             continue;
         }
 
-        if should_yield && re.is_match(line) {
+        if should_yield && NPM_SCRIPT_LINE.is_match(line) {
             let script = line.split_whitespace().next().unwrap().to_string();
             scripts.push(script);
         }
@@ -0,0 +1,216 @@
+use regex::Regex;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+/// Regular expression compiled the first time it is used, then shared by all the
+/// calls of the rules.
+pub struct LazyRegex {
+    pattern: &'static str,
+    regex: OnceLock<Regex>,
+}
+
+impl LazyRegex {
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: OnceLock::new(),
+        }
+    }
+
+    pub fn pattern(&self) -> &'static str {
+        self.pattern
+    }
+}
+
+impl Deref for LazyRegex {
+    type Target = Regex;
+
+    fn deref(&self) -> &Regex {
+        self.regex
+            .get_or_init(|| Regex::new(self.pattern).expect("invalid rule pattern"))
+    }
+}
+
+/// Declares the patterns of the registry, and `all_patterns` listing them.
+macro_rules! patterns {
+    ($($name:ident = $pattern:expr;)*) => {
+        $(pub static $name: LazyRegex = LazyRegex::new($pattern);)*
+
+        /// Returns every pattern of the registry.
+        pub fn all_patterns() -> Vec<&'static LazyRegex> {
+            vec![$(&$name),*]
+        }
+    };
+}
+
+// Patterns used by the rules, compiled once per process. They are all compiled by
+// the tests, so an invalid pattern fails there rather than on the user's machine.
+patterns! {
+    // aws_cli
+    AWS_CLI_INVALID_CHOICE = r"Invalid choice: '(.*)', maybe you meant:";
+    AWS_CLI_OPTIONS = r"(?m)^\s*\*\s(.*)";
+
+    // az_cli
+    AZ_CLI_MISTAKE = r"az(?:.*): '(.*)' is not in the '.*' command group.";
+    AZ_CLI_OPTIONS = r"(?m)^The most similar choice to '.*' is:\n\s*(.*)$";
+
+    // brew_install
+    BREW_INSTALL_DID_YOU_MEAN = "Warning: No available formula with the name \"(?:[^\"]+)\". Did you mean (.+)\\?";
+
+    // brew_reinstall
+    BREW_ALREADY_INSTALLED = r"Warning: .+ is already installed and up-to-date";
+    BREW_REINSTALL_HINT = r"To reinstall .+, run `brew reinstall [^`]+`";
+
+    // cargo_no_command
+    CARGO_DID_YOU_MEAN = r"Did you mean `([^`]*)`";
+
+    // cd_mkdir
+    CD_COMMAND = r"^cd (.*)";
+
+    // composer_not_command
+    COMPOSER_BROKEN_COMMAND = r#"Command "([^']*)" is not defined"#;
+    COMPOSER_DID_YOU_MEAN_THIS = r"Did you mean this\?[^\n]*\n\s*([^\n]*)";
+    COMPOSER_DID_YOU_MEAN_ONE_OF = r"Did you mean one of these\?[^\n]*\n\s*([^\n]*)";
+
+    // conda_mistype
+    CONDA_MISTYPE = r"'conda ([^']*)'";
+
+    // cp_omitting_directory
+    CP_COMMAND = r"^cp ";
+
+    // git_add
+    GIT_ADD_MISSING_FILE = r"error: pathspec '([^']*)' did not match any file\(s\) known to git.";
+
+    // git_bisect_usage
+    GIT_BISECT_BROKEN = r"git bisect ([^ $]*).*";
+    GIT_BISECT_USAGE = r"usage: git bisect \[([^\]]+)\]";
+
+    // git_branch_exists
+    GIT_BRANCH_EXISTS = r"fatal: A branch named '(.+)' already exists.";
+
+    // git_checkout
+    GIT_CHECKOUT_MISSING_PATHSPEC = r"error: pathspec '([^']*)' did not match any file\(s\) known to git";
+
+    // git_clone_missing
+    GIT_CLONE_URL = r"((git|ssh|http(s)?)|(git@[\w\.]+))(:(//)?)([\w\.@\:/\-~]+)(\.git)?(/)?";
+
+    // git_flag_after_filename
+    GIT_BAD_FLAG_AFTER_FILENAME = r"fatal: bad flag '(.*?)' used after filename";
+    GIT_OPTION_AFTER_FILENAME = r"fatal: option '(.*?)' must come before non-option arguments";
+
+    // git_lfs_mistype
+    GIT_LFS_UNKNOWN_COMMAND = r#"Error: unknown command "([^"]*)" for "git-lfs""#;
+
+    // git_merge
+    GIT_MERGE_UNKNOWN_BRANCH = r"merge: (.+) - not something we can merge";
+    GIT_MERGE_REMOTE_BRANCH = r"Did you mean this\?\n\t([^\n]+)";
+
+    // git_not_command
+    GIT_NOT_COMMAND = r"git: '([^']*)' is not a git command";
+
+    // git_push
+    GIT_PUSH_ARGUMENTS = r"git push (.*)";
+
+    // git_push_different_branch_names
+    GIT_PUSH_DIFFERENT_BRANCH_NAMES = r"(?m)^ +(git push [^\s]+ [^\s]+)";
+
+    // git_push_without_commits
+    GIT_PUSH_WITHOUT_COMMITS = r"src refspec \w+ does not match any";
+
+    // git_remote_delete
+    GIT_REMOTE_DELETE = r"delete";
+
+    // heroku_multiple_apps
+    HEROKU_MULTIPLE_APPS = r"([^ ]*) \([^)]*\)";
+
+    // heroku_not_command
+    HEROKU_NOT_COMMAND = r"Run heroku _ to run ([^.]*)";
+
+    // hostscli
+    HOSTSCLI_NO_SUCH_COMMAND = r#"Error: No such command "(.*)""#;
+
+    // lein_not_task
+    LEIN_NOT_TASK = r"'([^']*)' is not a task";
+
+    // ln_no_hard_link
+    LN_COMMAND = r"^ln ";
+
+    // long_form_help
+    LONG_FORM_HELP = r"(?:Run|Try) '([^']+)'(?: or '[^']+')? for (?:details|more information).";
+
+    // mercurial
+    MERCURIAL_DID_YOU_MEAN_ONE_OF = r"\n\(did you mean one of ([^\?]+)\?\)";
+    MERCURIAL_DID_YOU_MEAN = r"\n    ([^$]+)$";
+
+    // mkdir_p
+    MKDIR_COMMAND = r"\bmkdir (.*)";
+
+    // mvn_unknown_lifecycle_phase
+    MVN_UNKNOWN_LIFECYCLE_PHASE = r#"\[ERROR\] Unknown lifecycle phase \"(.+)\""#;
+    MVN_AVAILABLE_LIFECYCLE_PHASES = r"Available lifecycle phases are: (.+) -> \[Help 1\]";
+
+    // nixos_cmd_not_found
+    NIXOS_NIX_ENV_INSTALL = r"nix-env -iA ([^\s]*)";
+
+    // no_such_file
+    MV_NO_SUCH_FILE = r"mv: cannot move '[^']*' to '([^']*)': No such file or directory";
+    MV_NOT_A_DIRECTORY = r"mv: cannot move '[^']*' to '([^']*)': Not a directory";
+    CP_NO_SUCH_FILE = r"cp: cannot create regular file '([^']*)': No such file or directory";
+    CP_NOT_A_DIRECTORY = r"cp: cannot create regular file '([^']*)': Not a directory";
+
+    // npm_missing_script
+    NPM_MISSING_SCRIPT = r".*missing script: (.*)\n";
+
+    // pip_unknown_command
+    PIP_UNKNOWN_COMMAND = r#"ERROR: unknown command "([^"]+)""#;
+    PIP_MAYBE_YOU_MEANT = r#"maybe you meant "([^"]+)""#;
+
+    // python_module_error
+    PYTHON_MISSING_MODULE = r"ModuleNotFoundError: No module named '([^']+)'";
+
+    // rails_migrations_pending
+    RAILS_MIGRATIONS_SUGGESTION = r"To resolve this issue, run:\s+(.*?)\n";
+
+    // remove_shell_prompt_literal
+    SHELL_PROMPT_LITERAL = r"^[\s]*\$ [\S]+";
+
+    // rm_dir
+    RM_COMMAND = r"\brm (.*)";
+
+    // sudo_command_from_user_path
+    SUDO_COMMAND_NOT_FOUND = r"sudo: (.*): command not found";
+
+    // tmux
+    TMUX_AMBIGUOUS_COMMAND = r"ambiguous command: (.*), could be: (.*)";
+
+    // utils::git
+    GIT_ALIAS_EXPANSION = r"trace: alias expansion: ([^ ]*) => ([^\n]*)";
+
+    // utils::npm
+    NPM_SCRIPT_LINE = r"^  \S+";
+
+    // crate::utils
+    ANSI_SEQUENCE = r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-Z\\-_])";
+
+    // utils::messages
+    GIT_NOT_COMMAND_DE = r"(?m)^git: '([^']*)' ist kein Git-Befehl\. Siehe 'git --help'\.";
+    GIT_NOT_COMMAND_FR = r"(?m)^git ?: '([^']*)' n'est pas une commande git\. Voir 'git --help'\.";
+    GIT_NOT_COMMAND_ES = r"(?m)^git: '([^']*)' no es un comando de git\. Mira 'git --help'\.";
+    GIT_NOT_COMMAND_PT = r"(?m)^git: '([^']*)' não é um comando git\. Veja 'git --help'\.";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{all_patterns, Regex};
+
+    #[test]
+    fn test_all_patterns_compile() {
+        for pattern in all_patterns() {
+            assert!(
+                Regex::new(pattern.pattern()).is_ok(),
+                "invalid pattern: {}",
+                pattern.pattern()
+            );
+        }
+    }
+}
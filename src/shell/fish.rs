@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+use super::Shell;
+use crate::cli::command::shlex_split;
+use crate::{
+    ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_SHELL,
+    ENV_VAR_NAME_SHELL_ALIASES,
+};
+
+pub struct Fish;
+
+impl Shell for Fish {
+    fn get_shell(&self) -> String {
+        "fish".to_owned()
+    }
+
+    fn app_alias(&self, alias_name: &str) -> String {
+        format!(
+            r#"
+            function {alias_name} -d "Correct your previous console command"
+                set -l OC_CMD (
+                    env {ENV_VAR_NAME_SHELL}=fish {ENV_VAR_NAME_ALIAS}={alias_name} \
+                        {ENV_VAR_NAME_HISTORY}=$history[1] \
+                        {ENV_VAR_NAME_SHELL_ALIASES}=(alias | string collect) \
+                        ohcrab {ARGUMENT_PLACEHOLDER} $argv | string collect
+                )
+                and test -n "$OC_CMD"
+                and eval $OC_CMD
+            end
+            "#,
+        )
+    }
+
+    /// Extracts the command from a line of the fish history, which is stored as a
+    /// YAML-like list of entries:
+    ///
+    /// ```yaml
+    /// - cmd: ls -lah
+    ///   when: 1702325001
+    /// ```
+    fn script_from_history(&self, command_script: &str) -> String {
+        match command_script.strip_prefix("- cmd: ") {
+            Some(script) => unescape_history(script),
+            None => "".to_owned(),
+        }
+    }
+
+    fn get_history_file_name(&self) -> String {
+        let data_dir = match env::var("XDG_DATA_HOME") {
+            Ok(val) if !val.is_empty() => PathBuf::from(val),
+            _ => dirs::home_dir().unwrap().join(".local").join("share"),
+        };
+        data_dir
+            .join("fish")
+            .join("fish_history")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn and(&self, commands: Vec<&str>) -> String {
+        commands.join("; and ")
+    }
+
+    /// Parses the output of fish's `alias` function (`alias ll 'ls -l'`).
+    fn parse_aliases(&self, aliases: &str) -> HashMap<String, String> {
+        aliases
+            .lines()
+            .filter_map(|line| {
+                let mut parts = shlex_split(line.trim().strip_prefix("alias ")?).into_iter();
+                let name = parts.next()?;
+                Some((name, parts.collect::<Vec<_>>().join(" ")))
+            })
+            .filter(|(name, value)| !value.is_empty())
+            .collect()
+    }
+
+    fn get_builtin_commands(&self) -> Vec<String> {
+        [
+            "abbr",
+            "and",
+            "argparse",
+            "begin",
+            "bg",
+            "bind",
+            "block",
+            "break",
+            "breakpoint",
+            "builtin",
+            "case",
+            "cd",
+            "command",
+            "commandline",
+            "complete",
+            "contains",
+            "continue",
+            "count",
+            "disown",
+            "echo",
+            "else",
+            "emit",
+            "end",
+            "eval",
+            "exec",
+            "exit",
+            "false",
+            "fg",
+            "for",
+            "function",
+            "functions",
+            "history",
+            "if",
+            "jobs",
+            "math",
+            "not",
+            "or",
+            "path",
+            "printf",
+            "pwd",
+            "random",
+            "read",
+            "realpath",
+            "return",
+            "set",
+            "set_color",
+            "source",
+            "status",
+            "string",
+            "switch",
+            "test",
+            "time",
+            "true",
+            "type",
+            "ulimit",
+            "wait",
+            "while",
+        ]
+        .iter()
+        .map(|builtin| builtin.to_string())
+        .collect()
+    }
+}
+
+/// Reverts the escaping fish applies to backslashes and new lines in the history file.
+fn unescape_history(script: &str) -> String {
+    let mut unescaped = String::with_capacity(script.len());
+    let mut chars = script.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(next) => unescaped.push(next),
+                None => unescaped.push(c),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fish;
+    use crate::shell::Shell;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_get_history() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "- cmd: ls -lah\n  when: 1702325001\n- cmd: cd /tmp\n  when: 1702325002\n  paths:\n    - /tmp\n- cmd: echo \\\\n\n  when: 1702325003"
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            Fish {}.get_history(Some(path)),
+            vec!["ls -lah", "cd /tmp", "echo \\n"]
+        );
+    }
+
+    #[test]
+    fn test_and() {
+        assert_eq!(
+            Fish {}.and(vec!["git add .", "git commit"]),
+            "git add .; and git commit"
+        );
+    }
+
+    #[test]
+    fn test_app_alias() {
+        let alias = Fish {}.app_alias("crab");
+        assert!(alias.contains("function crab"));
+        assert!(alias.contains("$history[1]"));
+        assert!(alias.contains("OHCRAB_SHELL=fish"));
+    }
+
+    #[test]
+    fn test_get_builtin_commands() {
+        let builtins = Fish {}.get_builtin_commands();
+        assert!(builtins.contains(&"string".to_owned()));
+        assert!(!builtins.contains(&"shopt".to_owned()));
+    }
+}
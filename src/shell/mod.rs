@@ -1,14 +1,42 @@
+use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-use crate::{ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_SHELL};
+use shlex::Quoter;
 
-pub trait Shell {
+use crate::cli::command::shlex_split;
+use crate::cli::instant_mode::OUTPUT_MARK_PRINTF;
+use crate::config::get_cache_dir;
+use crate::{
+    ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_OUTPUT_LOG,
+    ENV_VAR_NAME_SHELL, ENV_VAR_NAME_SHELL_ALIASES,
+};
+
+mod fish;
+mod nushell;
+mod powershell;
+mod tcsh;
+
+pub use fish::Fish;
+pub use nushell::Nushell;
+pub use powershell::PowerShell;
+pub use tcsh::Tcsh;
+
+/// `Send` and `Sync` so the rules can share the shell between their threads.
+pub trait Shell: Send + Sync {
     fn app_alias(&self, alias_name: &str) -> String;
     fn get_shell(&self) -> String;
     fn get_history_file_name(&self) -> String;
+
+    /// Returns the shell code enabling the instant mode, printed after the shell
+    /// function: the session is logged with `script` and a mark is printed before
+    /// each prompt, so the output of the last command can be read from the log.
+    /// `None` if the shell does not support it.
+    fn instant_mode_alias(&self) -> Option<String> {
+        None
+    }
     fn script_from_history(&self, command_script: &str) -> String {
         command_script.to_owned()
     }
@@ -21,27 +49,41 @@ pub trait Shell {
                 self.get_history_file_name()
             }
         };
-        let mut history: Vec<String> = Vec::new();
-        if Path::new(&history_file_name).exists() {
-            if let Ok(file) = File::open(&history_file_name) {
-                let reader = io::BufReader::new(file);
-
-                // TODO: Limit history length based on settings
-                for line in reader.lines() {
-                    let prepared = self.script_from_history(&line.unwrap_or("".to_owned()));
-                    let prepared = prepared.trim();
-                    if !prepared.is_empty() {
-                        history.push(prepared.to_owned());
-                    }
-                }
-            }
-        }
-        history
+        read_history_file(self, &history_file_name)
     }
     fn and(&self, commands: Vec<&str>) -> String {
         commands.join(" && ")
     }
 
+    /// Returns the aliases defined in the user's shell, exported by the shell function
+    /// generated with `app_alias`.
+    fn get_aliases(&self) -> HashMap<String, String> {
+        match env::var(ENV_VAR_NAME_SHELL_ALIASES) {
+            Ok(aliases) => self.parse_aliases(&aliases),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Parses the output of the `alias` builtin. Both the bash (`alias ll='ls -l'`) and
+    /// the zsh (`ll='ls -l'`) formats are supported.
+    fn parse_aliases(&self, aliases: &str) -> HashMap<String, String> {
+        aliases
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix("alias ").unwrap_or(line);
+                let (name, value) = line.split_once('=')?;
+                Some((name.trim().to_owned(), shlex_split(value).join(" ")))
+            })
+            .filter(|(name, value)| !name.is_empty() && !value.is_empty())
+            .collect()
+    }
+
+    /// Expands the shell alias used as the first word of the script, if any.
+    fn expand_aliases(&self, command_script: &str) -> String {
+        replace_alias(command_script, &self.get_aliases())
+    }
+
     fn get_builtin_commands(&self) -> Vec<String> {
         vec![
             "alias".to_owned(),
@@ -104,11 +146,71 @@ pub trait Shell {
     }
 }
 
+/// Restarts the interactive shell inside `script`, logging the session to a file of
+/// `~/.cache/ohcrab/sessions`, unless it is already logged. As the log holds
+/// everything shown in the terminal, the directory is only readable by the user and
+/// the log is removed when the session ends.
+fn start_logged_session() -> String {
+    let log_dir = get_cache_dir().join("sessions");
+    let log_dir = Quoter::new()
+        .quote(&log_dir.to_string_lossy())
+        .map(|quoted| quoted.into_owned())
+        .unwrap_or_default();
+    format!(
+        r#"
+            if [ -z "${ENV_VAR_NAME_OUTPUT_LOG}" ] && [ -t 1 ] && command -v script > /dev/null && mkdir -p -m 700 {log_dir} && chmod 700 {log_dir}; then
+                export {ENV_VAR_NAME_OUTPUT_LOG}="$(mktemp {log_dir}/session-XXXXXX)";
+                if [ "$(uname)" = "Darwin" ]; then
+                    script -qF "${ENV_VAR_NAME_OUTPUT_LOG}";
+                else
+                    script -qf "${ENV_VAR_NAME_OUTPUT_LOG}";
+                fi;
+                rm -f "${ENV_VAR_NAME_OUTPUT_LOG}";
+                exit;
+            fi;
+            "#,
+    )
+}
+
+/// Replaces the first word of `command_script` by its expansion when it is one of
+/// the given aliases.
+fn replace_alias(command_script: &str, aliases: &HashMap<String, String>) -> String {
+    let binary = command_script.split_whitespace().next().unwrap_or("");
+    match aliases.get(binary) {
+        Some(expansion) if expansion != binary => command_script.replacen(binary, expansion, 1),
+        _ => command_script.to_owned(),
+    }
+}
+
+/// Reads a history file line by line, extracting the commands with
+/// `Shell::script_from_history`.
+fn read_history_file<S: Shell + ?Sized>(shell: &S, history_file_name: &str) -> Vec<String> {
+    let mut history: Vec<String> = Vec::new();
+    if Path::new(history_file_name).exists() {
+        if let Ok(file) = File::open(history_file_name) {
+            let reader = io::BufReader::new(file);
+
+            for line in reader.lines() {
+                let prepared = shell.script_from_history(&line.unwrap_or("".to_owned()));
+                let prepared = prepared.trim();
+                if !prepared.is_empty() {
+                    history.push(prepared.to_owned());
+                }
+            }
+        }
+    }
+    history
+}
+
 pub fn get_bash_type(shell_type: &str) -> Box<dyn Shell> {
     let shell_candidate = shell_type.to_lowercase();
     match shell_candidate.as_str() {
         "zsh" => Box::new(Zsh),
         "bash" => Box::new(Bash),
+        "fish" => Box::new(Fish),
+        "nu" | "nushell" => Box::new(Nushell),
+        "pwsh" | "powershell" => Box::new(PowerShell),
+        "tcsh" | "csh" => Box::new(Tcsh),
         _ => panic!("The shell '{shell_type}' is not supported yet"),
     }
 }
@@ -128,15 +230,28 @@ impl Shell for Zsh {
                 export {ENV_VAR_NAME_SHELL}="zsh";
                 export {ENV_VAR_NAME_ALIAS}="{alias_name}";
                 export {ENV_VAR_NAME_HISTORY}="$(fc -ln -1)";
+                export {ENV_VAR_NAME_SHELL_ALIASES}="$(alias)";
                 OC_CMD=$(
                     ohcrab {ARGUMENT_PLACEHOLDER} $@
                 ) && eval $OC_CMD;
-                unset {ENV_VAR_NAME_HISTORY};
-            }}
+                unset {ENV_VAR_NAME_HISTORY} {ENV_VAR_NAME_SHELL_ALIASES};
+            }};
             "#,
         )
     }
 
+    fn instant_mode_alias(&self) -> Option<String> {
+        Some(format!(
+            r#"{}
+            _ohcrab_mark () {{
+                printf '{OUTPUT_MARK_PRINTF}';
+            }};
+            precmd_functions+=(_ohcrab_mark);
+            "#,
+            start_logged_session(),
+        ))
+    }
+
     fn script_from_history(&self, command_script: &str) -> String {
         if command_script.contains(';') {
             command_script.split_once(';').unwrap().1.to_owned()
@@ -170,15 +285,25 @@ impl Shell for Bash {
                 export {ENV_VAR_NAME_SHELL}="bash";
                 export {ENV_VAR_NAME_ALIAS}="{alias_name}";
                 export {ENV_VAR_NAME_HISTORY}="$(fc -ln -1)";
+                export {ENV_VAR_NAME_SHELL_ALIASES}="$(alias)";
                 OC_CMD=$(
                     ohcrab {ARGUMENT_PLACEHOLDER} "$@"
                 ) && eval "$OC_CMD";
-                unset {ENV_VAR_NAME_HISTORY};
-            }}
+                unset {ENV_VAR_NAME_HISTORY} {ENV_VAR_NAME_SHELL_ALIASES};
+            }};
             "#,
         )
     }
 
+    fn instant_mode_alias(&self) -> Option<String> {
+        Some(format!(
+            r#"{}
+            PROMPT_COMMAND="printf '{OUTPUT_MARK_PRINTF}';${{PROMPT_COMMAND}}";
+            "#,
+            start_logged_session(),
+        ))
+    }
+
     fn get_history_file_name(&self) -> String {
         match env::var("HISTFILE") {
             Ok(val) => val,
@@ -219,3 +344,80 @@ mod test_zsh {
         );
     }
 }
+
+#[cfg(test)]
+mod test_aliases {
+    use std::collections::HashMap;
+
+    use super::{replace_alias, Bash, Fish, Shell, Tcsh, Zsh};
+
+    #[test]
+    fn test_parse_aliases_bash() {
+        assert_eq!(
+            Bash {}.parse_aliases(
+                r#"alias gst='git status'
+alias ll='ls -l'
+alias say='echo '\''hi'\'''
+"#
+            ),
+            HashMap::from([
+                ("gst".to_owned(), "git status".to_owned()),
+                ("ll".to_owned(), "ls -l".to_owned()),
+                ("say".to_owned(), "echo 'hi'".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_aliases_zsh() {
+        assert_eq!(
+            Zsh {}.parse_aliases("g=git\ngst='git status'\n-='cd -'\nwhich-command=whence"),
+            HashMap::from([
+                ("g".to_owned(), "git".to_owned()),
+                ("gst".to_owned(), "git status".to_owned()),
+                ("-".to_owned(), "cd -".to_owned()),
+                ("which-command".to_owned(), "whence".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_aliases_fish() {
+        assert_eq!(
+            Fish {}.parse_aliases("alias gst 'git status'\nalias ll 'ls -l'\nalias g git"),
+            HashMap::from([
+                ("gst".to_owned(), "git status".to_owned()),
+                ("ll".to_owned(), "ls -l".to_owned()),
+                ("g".to_owned(), "git".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_replace_alias() {
+        let aliases = HashMap::from([
+            ("gst".to_owned(), "git status".to_owned()),
+            ("ls".to_owned(), "ls".to_owned()),
+        ]);
+        assert_eq!(replace_alias("gst -sb", &aliases), "git status -sb");
+        assert_eq!(replace_alias("gst", &aliases), "git status");
+        assert_eq!(replace_alias("ls gst", &aliases), "ls gst");
+        assert_eq!(replace_alias("gstt", &aliases), "gstt");
+        assert_eq!(replace_alias("", &aliases), "");
+    }
+
+    #[test]
+    fn test_instant_mode_alias() {
+        let bash_alias = Bash {}.instant_mode_alias().unwrap();
+        assert!(bash_alias.contains("script -qf \"$OHCRAB_OUTPUT_LOG\""));
+        assert!(bash_alias.contains("PROMPT_COMMAND=\"printf '\\033]7770;ohcrab\\007';"));
+
+        let zsh_alias = Zsh {}.instant_mode_alias().unwrap();
+        assert!(zsh_alias.contains("export OHCRAB_OUTPUT_LOG="));
+        assert!(zsh_alias.contains("mkdir -p -m 700 "));
+        assert!(zsh_alias.contains("/sessions/session-XXXXXX)\""));
+        assert!(zsh_alias.contains("precmd_functions+=(_ohcrab_mark)"));
+
+        assert!(Tcsh {}.instant_mode_alias().is_none());
+    }
+}
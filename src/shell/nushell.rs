@@ -0,0 +1,289 @@
+use std::path::{Path, PathBuf};
+
+use super::{read_history_file, Shell};
+use crate::logger;
+use crate::{ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_SHELL};
+
+const PLAINTEXT_HISTORY_FILE: &str = "history.txt";
+const SQLITE_HISTORY_FILE: &str = "history.sqlite3";
+
+pub struct Nushell;
+
+impl Nushell {
+    fn get_config_dir(&self) -> PathBuf {
+        dirs::config_dir().unwrap_or_default().join("nushell")
+    }
+}
+
+impl Shell for Nushell {
+    fn get_shell(&self) -> String {
+        "nu".to_owned()
+    }
+
+    /// Nushell cannot `eval` a string, so the corrected command is run with `nu -c`.
+    /// A corrected command that is a single `cd` is run by the function itself
+    /// (defined with `--env`), otherwise the change of directory would be lost with
+    /// the child process.
+    fn app_alias(&self, alias_name: &str) -> String {
+        format!(
+            r#"
+            def --env {alias_name} [...args] {{
+                let oc_cmd = (with-env {{
+                    {ENV_VAR_NAME_SHELL}: "nu",
+                    {ENV_VAR_NAME_ALIAS}: "{alias_name}",
+                    {ENV_VAR_NAME_HISTORY}: (history | last 2 | first | get command)
+                }} {{ ^ohcrab {ARGUMENT_PLACEHOLDER} ...$args }} | str trim)
+                if ($oc_cmd | is-empty) {{
+                    return
+                }}
+                if ($oc_cmd =~ '^cd\s+[^;|&]+$') {{
+                    cd ($oc_cmd | str replace --regex '^cd\s+' '')
+                }} else {{
+                    ^nu -c $oc_cmd
+                }}
+            }}
+            "#,
+        )
+    }
+
+    /// Multi-line commands are stored in the plaintext history with the new lines
+    /// replaced by `<\n>`.
+    fn script_from_history(&self, command_script: &str) -> String {
+        command_script.replace("<\\n>", "\n")
+    }
+
+    /// Returns the SQLite history file when it exists (and ohcrab was built with the
+    /// `sqlite` feature, enabled by default), otherwise the plaintext one.
+    fn get_history_file_name(&self) -> String {
+        let sqlite_history = self.get_config_dir().join(SQLITE_HISTORY_FILE);
+        if sqlite_history.exists() {
+            if cfg!(feature = "sqlite") {
+                return sqlite_history.to_str().unwrap().to_string();
+            }
+            logger::warn(&format!(
+                "Skipping {}: ohcrab was built without the `sqlite` feature",
+                sqlite_history.display()
+            ));
+        }
+        let history_file = self.get_config_dir().join(PLAINTEXT_HISTORY_FILE);
+        history_file.to_str().unwrap().to_string()
+    }
+
+    fn get_history(&self, file_path: Option<&str>) -> Vec<String> {
+        let history_file_name = {
+            if let Some(path) = file_path {
+                path.to_owned()
+            } else {
+                self.get_history_file_name()
+            }
+        };
+        if history_file_name.ends_with(SQLITE_HISTORY_FILE) {
+            read_sqlite_history(Path::new(&history_file_name))
+        } else {
+            read_history_file(self, &history_file_name)
+        }
+    }
+
+    /// Nushell stops a block at the first failing command, so `;` behaves like `&&`.
+    fn and(&self, commands: Vec<&str>) -> String {
+        commands.join("; ")
+    }
+
+    fn get_builtin_commands(&self) -> Vec<String> {
+        [
+            "alias",
+            "all",
+            "any",
+            "append",
+            "cd",
+            "clear",
+            "compact",
+            "cp",
+            "def",
+            "describe",
+            "do",
+            "du",
+            "each",
+            "echo",
+            "enumerate",
+            "error",
+            "exit",
+            "export",
+            "filter",
+            "find",
+            "first",
+            "flatten",
+            "for",
+            "from",
+            "get",
+            "glob",
+            "group-by",
+            "help",
+            "hide",
+            "history",
+            "http",
+            "if",
+            "input",
+            "insert",
+            "is-empty",
+            "items",
+            "join",
+            "kill",
+            "last",
+            "length",
+            "let",
+            "lines",
+            "load-env",
+            "loop",
+            "ls",
+            "match",
+            "merge",
+            "mkdir",
+            "module",
+            "mut",
+            "mv",
+            "open",
+            "overlay",
+            "par-each",
+            "parse",
+            "path",
+            "print",
+            "ps",
+            "reduce",
+            "reject",
+            "rename",
+            "return",
+            "reverse",
+            "rm",
+            "save",
+            "select",
+            "seq",
+            "skip",
+            "sleep",
+            "sort",
+            "sort-by",
+            "source",
+            "split",
+            "start",
+            "str",
+            "sys",
+            "table",
+            "take",
+            "to",
+            "touch",
+            "try",
+            "uniq",
+            "update",
+            "upsert",
+            "use",
+            "where",
+            "which",
+            "while",
+            "with-env",
+            "wrap",
+        ]
+        .iter()
+        .map(|builtin| builtin.to_string())
+        .collect()
+    }
+}
+
+/// Reads the commands from nushell's SQLite history, oldest first.
+#[cfg(feature = "sqlite")]
+fn read_sqlite_history(history_file: &Path) -> Vec<String> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let commands = Connection::open_with_flags(history_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|connection| {
+            let mut statement =
+                connection.prepare("SELECT command_line FROM history ORDER BY id")?;
+            let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+            Ok(rows.flatten().collect::<Vec<_>>())
+        })
+        .unwrap_or_default();
+    commands
+        .iter()
+        .map(|command| command.trim().to_owned())
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn read_sqlite_history(_: &Path) -> Vec<String> {
+    // Reading the SQLite history requires the `sqlite` feature.
+    vec![]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Nushell;
+    use crate::cli::command::shell_command;
+    use crate::shell::Shell;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_get_history() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "ls | where size > 1kb\ncd /tmp\nif true {{<\\n>  print ok<\\n>}}"
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            Nushell {}.get_history(Some(path)),
+            vec![
+                "ls | where size > 1kb",
+                "cd /tmp",
+                "if true {\n  print ok\n}"
+            ]
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_get_history_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.sqlite3");
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE history (id INTEGER PRIMARY KEY, command_line TEXT NOT NULL);
+                INSERT INTO history (id, command_line) VALUES (2, 'cd /tmp');
+                INSERT INTO history (id, command_line) VALUES (1, 'ls');
+                INSERT INTO history (id, command_line) VALUES (3, 'git status');",
+            )
+            .unwrap();
+
+        assert_eq!(
+            Nushell {}.get_history(Some(path.to_str().unwrap())),
+            vec!["ls", "cd /tmp", "git status"]
+        );
+    }
+
+    #[test]
+    fn test_and() {
+        assert_eq!(
+            Nushell {}.and(vec!["git add .", "git commit"]),
+            "git add .; git commit"
+        );
+    }
+
+    #[test]
+    fn test_app_alias() {
+        let alias = Nushell {}.app_alias("crab");
+        assert!(alias.contains("def --env crab [...args]"));
+        assert!(alias.contains("OHCRAB_SHELL: \"nu\""));
+        // Only a single `cd` is run by the function, not a chain ending with one
+        assert!(alias.contains("if ($oc_cmd =~ '^cd\\s+[^;|&]+$')"));
+    }
+
+    #[test]
+    fn test_shell_command() {
+        let cmd = shell_command(&Nushell {}.get_shell());
+        assert_eq!(cmd.get_program(), "nu");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), vec!["-c"]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::env;
+use std::path::PathBuf;
+
+use super::Shell;
+use crate::{ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_SHELL};
+
+/// Aliases that PowerShell only defines on Windows. Elsewhere, these names run the
+/// native commands.
+const WINDOWS_ALIASES: [&str; 7] = ["cat", "clear", "cp", "ls", "mv", "rm", "rmdir"];
+
+pub struct PowerShell;
+
+impl Shell for PowerShell {
+    fn get_shell(&self) -> String {
+        "pwsh".to_owned()
+    }
+
+    fn app_alias(&self, alias_name: &str) -> String {
+        format!(
+            r#"
+            function {alias_name} {{
+                $env:{ENV_VAR_NAME_SHELL} = "pwsh";
+                $env:{ENV_VAR_NAME_ALIAS} = "{alias_name}";
+                $env:{ENV_VAR_NAME_HISTORY} = (Get-History -Count 1).CommandLine;
+                if (-not [string]::IsNullOrWhiteSpace($env:{ENV_VAR_NAME_HISTORY})) {{
+                    $OC_CMD = (ohcrab {ARGUMENT_PLACEHOLDER} @args) -join "`n";
+                    if ($? -and -not [string]::IsNullOrWhiteSpace($OC_CMD)) {{
+                        Invoke-Expression $OC_CMD;
+                    }}
+                }}
+                Remove-Item Env:{ENV_VAR_NAME_HISTORY} -ErrorAction SilentlyContinue;
+                [Console]::ResetColor();
+            }}
+            "#,
+        )
+    }
+
+    /// Returns the path of the history file written by PSReadLine.
+    fn get_history_file_name(&self) -> String {
+        let history_dir = if cfg!(target_os = "windows") {
+            PathBuf::from(env::var("APPDATA").unwrap_or_default())
+                .join("Microsoft")
+                .join("Windows")
+                .join("PowerShell")
+        } else {
+            match env::var("XDG_DATA_HOME") {
+                Ok(val) if !val.is_empty() => PathBuf::from(val),
+                _ => dirs::home_dir().unwrap().join(".local").join("share"),
+            }
+            .join("powershell")
+        };
+        history_dir
+            .join("PSReadLine")
+            .join("ConsoleHost_history.txt")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// Chains the commands so each one only runs if the previous one succeeded.
+    ///
+    /// `&&` is not available before PowerShell 7, so `$?` is checked explicitly.
+    fn and(&self, commands: Vec<&str>) -> String {
+        let mut commands = commands.into_iter().rev();
+        let last = commands.next().unwrap_or_default().to_owned();
+        commands.fold(last, |chained, command| {
+            format!("{command}; if ($?) {{ {chained} }}")
+        })
+    }
+
+    fn get_builtin_commands(&self) -> Vec<String> {
+        let mut builtins = vec![
+            "cd",
+            "cls",
+            "copy",
+            "del",
+            "dir",
+            "echo",
+            "exit",
+            "foreach",
+            "function",
+            "history",
+            "if",
+            "iex",
+            "mkdir",
+            "move",
+            "popd",
+            "pushd",
+            "pwd",
+            "return",
+            "type",
+            "where",
+            "while",
+            "Clear-Host",
+            "Copy-Item",
+            "ForEach-Object",
+            "Get-ChildItem",
+            "Get-Command",
+            "Get-Content",
+            "Get-Help",
+            "Get-History",
+            "Get-Item",
+            "Get-Location",
+            "Invoke-Expression",
+            "Move-Item",
+            "New-Item",
+            "Remove-Item",
+            "Select-Object",
+            "Select-String",
+            "Set-Content",
+            "Set-Location",
+            "Sort-Object",
+            "Where-Object",
+            "Write-Host",
+            "Write-Output",
+        ];
+        if cfg!(windows) {
+            builtins.extend(WINDOWS_ALIASES);
+        }
+        builtins.iter().map(|builtin| builtin.to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PowerShell;
+    use crate::cli::command::run_command;
+    use crate::config::Settings;
+    use crate::shell::Shell;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_get_history() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "Get-ChildItem\ncd /tmp\n\ngit status").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            PowerShell {}.get_history(Some(path)),
+            vec!["Get-ChildItem", "cd /tmp", "git status"]
+        );
+    }
+
+    #[test]
+    fn test_and() {
+        assert_eq!(PowerShell {}.and(vec!["git add ."]), "git add .");
+        assert_eq!(
+            PowerShell {}.and(vec!["git add .", "git commit", "git push"]),
+            "git add .; if ($?) { git commit; if ($?) { git push } }"
+        );
+    }
+
+    #[test]
+    fn test_app_alias() {
+        let alias = PowerShell {}.app_alias("crab");
+        assert!(alias.contains("function crab {"));
+        assert!(alias.contains("Get-History -Count 1"));
+        assert!(alias.contains("$env:OHCRAB_SHELL = \"pwsh\""));
+    }
+
+    #[test]
+    fn test_get_builtin_commands() {
+        let builtins = PowerShell {}.get_builtin_commands();
+        assert!(builtins.contains(&"Get-ChildItem".to_owned()));
+        assert_eq!(builtins.contains(&"ls".to_owned()), cfg!(windows));
+        assert_eq!(builtins.contains(&"rm".to_owned()), cfg!(windows));
+    }
+
+    #[test]
+    #[ignore = "requires PowerShell (pwsh)"]
+    fn test_run_command() {
+        let crab_command = run_command(
+            vec!["Write-Output".to_owned(), "Hello!".to_owned()],
+            &PowerShell {},
+            &Settings::default(),
+        );
+        assert_eq!(crab_command.output.unwrap().trim(), "Hello!");
+    }
+}
@@ -0,0 +1,170 @@
+use super::Shell;
+use crate::{ARGUMENT_PLACEHOLDER, ENV_VAR_NAME_ALIAS, ENV_VAR_NAME_HISTORY, ENV_VAR_NAME_SHELL};
+
+pub struct Tcsh;
+
+impl Shell for Tcsh {
+    fn get_shell(&self) -> String {
+        "tcsh".to_owned()
+    }
+
+    /// tcsh cannot define functions, so the whole logic is chained in a single alias.
+    /// `history -h 2` lists the failed command followed by the call to the alias.
+    fn app_alias(&self, alias_name: &str) -> String {
+        format!(
+            "alias {alias_name} 'setenv {ENV_VAR_NAME_SHELL} tcsh \
+            && setenv {ENV_VAR_NAME_ALIAS} {alias_name} \
+            && setenv {ENV_VAR_NAME_HISTORY} \"`history -h 2 | head -n 1`\" \
+            && eval `ohcrab {ARGUMENT_PLACEHOLDER} \\!*`; \
+            unsetenv {ENV_VAR_NAME_HISTORY}'"
+        )
+    }
+
+    /// Skips the `#+<timestamp>` lines written to the history file when `savehist` is
+    /// set with the `merge` option.
+    fn script_from_history(&self, command_script: &str) -> String {
+        if command_script.starts_with("#+") {
+            "".to_owned()
+        } else {
+            command_script.to_owned()
+        }
+    }
+
+    fn get_history_file_name(&self) -> String {
+        dirs::home_dir()
+            .unwrap()
+            .join(".history")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn get_builtin_commands(&self) -> Vec<String> {
+        [
+            "alias",
+            "alloc",
+            "bg",
+            "bindkey",
+            "break",
+            "breaksw",
+            "builtins",
+            "bye",
+            "case",
+            "cd",
+            "chdir",
+            "complete",
+            "continue",
+            "default",
+            "dirs",
+            "echo",
+            "echotc",
+            "else",
+            "end",
+            "endif",
+            "endsw",
+            "eval",
+            "exec",
+            "exit",
+            "fg",
+            "filetest",
+            "foreach",
+            "glob",
+            "goto",
+            "hashstat",
+            "history",
+            "hup",
+            "if",
+            "jobs",
+            "kill",
+            "limit",
+            "log",
+            "login",
+            "logout",
+            "ls-F",
+            "nice",
+            "nohup",
+            "notify",
+            "onintr",
+            "popd",
+            "printenv",
+            "pushd",
+            "rehash",
+            "repeat",
+            "sched",
+            "set",
+            "setenv",
+            "settc",
+            "setty",
+            "shift",
+            "source",
+            "stop",
+            "suspend",
+            "switch",
+            "telltc",
+            "time",
+            "umask",
+            "unalias",
+            "uncomplete",
+            "unhash",
+            "unlimit",
+            "unset",
+            "unsetenv",
+            "wait",
+            "where",
+            "which",
+            "while",
+        ]
+        .iter()
+        .map(|builtin| builtin.to_string())
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tcsh;
+    use crate::shell::Shell;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_get_history() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "#+1702325001\nls -lah\n#+1702325002\ncd /tmp\n#+1702325003\nmodule load gcc"
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            Tcsh {}.get_history(Some(path)),
+            vec!["ls -lah", "cd /tmp", "module load gcc"]
+        );
+    }
+
+    #[test]
+    fn test_and() {
+        assert_eq!(
+            Tcsh {}.and(vec!["git add .", "git commit"]),
+            "git add . && git commit"
+        );
+    }
+
+    #[test]
+    fn test_app_alias() {
+        let alias = Tcsh {}.app_alias("crab");
+        assert!(alias
+            .starts_with("alias crab 'setenv OHCRAB_SHELL tcsh && setenv OHCRAB_ALIAS crab && "));
+        assert!(alias.contains("setenv OHCRAB_COMMAND_HISTORY \"`history -h 2 | head -n 1`\""));
+        assert!(alias.contains("eval `ohcrab OHCRAB_ARGUMENT_PLACEHOLDER \\!*`"));
+        assert!(alias.ends_with("unsetenv OHCRAB_COMMAND_HISTORY'"));
+    }
+
+    #[test]
+    fn test_get_builtin_commands() {
+        let builtins = Tcsh {}.get_builtin_commands();
+        assert!(builtins.contains(&"setenv".to_owned()));
+        assert!(!builtins.contains(&"export".to_owned()));
+    }
+}
@@ -0,0 +1,379 @@
+use console::{measure_text_width, style, Key, Term};
+use std::fs;
+use std::path::Path;
+
+use super::terminal_size;
+use crate::config::get_cache_dir;
+use crate::logger;
+
+const HISTORY_FILE_NAME: &str = "edit_history";
+/// Number of edited commands kept in the history file.
+const MAX_HISTORY_SIZE: usize = 100;
+const PROMPT: &str = "✎ ";
+
+/// What the user did with the edited line.
+#[derive(Debug, PartialEq)]
+pub enum EditOutcome {
+    /// The line was confirmed with `enter`.
+    Accept(String),
+    /// The edition was cancelled with `esc`.
+    Cancel,
+    /// `Ctrl+C` was pressed, or the terminal is gone.
+    Abort,
+}
+
+/// Where the line is drawn, relative to the start of the prompt (the line wraps
+/// when it is wider than the terminal).
+#[derive(Debug, PartialEq)]
+struct Layout {
+    end_row: usize,
+    end_column: usize,
+    cursor_row: usize,
+    cursor_column: usize,
+}
+
+/// State of a single line being edited: its characters, the position of the cursor
+/// and the previous edits that `↑`/`↓` bring back.
+#[derive(Debug)]
+struct LineEditor {
+    chars: Vec<char>,
+    cursor: usize,
+    /// Previous edits, oldest first, followed by the line being edited.
+    entries: Vec<String>,
+    entry: usize,
+}
+
+impl LineEditor {
+    fn new(text: &str, history: Vec<String>) -> Self {
+        let mut entries = history;
+        entries.push(text.to_owned());
+        let mut editor = Self {
+            chars: vec![],
+            cursor: 0,
+            entry: entries.len() - 1,
+            entries,
+        };
+        editor.load_entry();
+        editor
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Returns where the line is drawn after a prompt `prompt_width` columns wide, on a
+    /// terminal `term_width` columns wide. The positions are in display columns, not
+    /// characters (e.g. CJK characters take two columns).
+    fn layout(&self, prompt_width: usize, term_width: usize) -> Layout {
+        let position = |chars: &[char]| {
+            let width = prompt_width + measure_text_width(&chars.iter().collect::<String>());
+            (width / term_width, width % term_width)
+        };
+        let (end_row, end_column) = position(&self.chars);
+        let (cursor_row, cursor_column) = position(&self.chars[..self.cursor]);
+        Layout {
+            end_row,
+            end_column,
+            cursor_row,
+            cursor_column,
+        }
+    }
+
+    /// Replaces the line with the current history entry, the cursor at its end.
+    fn load_entry(&mut self) {
+        self.chars = self.entries[self.entry].chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    /// Moves in the history, keeping the changes made to the entry being left.
+    fn browse_history(&mut self, forward: bool) {
+        let entry = if forward {
+            self.entry + 1
+        } else {
+            self.entry.wrapping_sub(1)
+        };
+        if entry < self.entries.len() {
+            self.entries[self.entry] = self.text();
+            self.entry = entry;
+            self.load_entry();
+        }
+    }
+
+    /// Returns the position of the start of the word before the cursor, skipping the
+    /// spaces before it.
+    fn previous_word_start(&self) -> usize {
+        let mut position = self.cursor;
+        while position > 0 && self.chars[position - 1].is_whitespace() {
+            position -= 1;
+        }
+        while position > 0 && !self.chars[position - 1].is_whitespace() {
+            position -= 1;
+        }
+        position
+    }
+
+    /// Returns the position of the end of the word after the cursor, skipping the
+    /// spaces before it.
+    fn next_word_end(&self) -> usize {
+        let mut position = self.cursor;
+        while position < self.chars.len() && self.chars[position].is_whitespace() {
+            position += 1;
+        }
+        while position < self.chars.len() && !self.chars[position].is_whitespace() {
+            position += 1;
+        }
+        position
+    }
+
+    fn delete_previous_word(&mut self) {
+        let start = self.previous_word_start();
+        self.chars.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    /// Applies a key to the line, returning the outcome if the edition is over.
+    ///
+    /// The keys follow the usual shell bindings: `Ctrl+A`/`Ctrl+E` (or `home`/`end`)
+    /// go to the start and the end of the line, `Ctrl+W` deletes the word before the
+    /// cursor (as does `Alt+Backspace`), `Ctrl+U` and `Ctrl+K` everything before and
+    /// after it. `Alt+B` and `Alt+F` move by word.
+    fn handle_key(&mut self, key: Key) -> Option<EditOutcome> {
+        match key {
+            Key::Enter if !self.text().trim().is_empty() => {
+                return Some(EditOutcome::Accept(self.text()))
+            }
+            Key::Escape => return Some(EditOutcome::Cancel),
+            Key::ArrowLeft => self.cursor = self.cursor.saturating_sub(1),
+            Key::ArrowRight => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Key::Home | Key::Char('\x01') => self.cursor = 0,
+            Key::End | Key::Char('\x05') => self.cursor = self.chars.len(),
+            // Alt+B and Alt+F
+            Key::UnknownEscSeq(seq) if seq == ['b'] => self.cursor = self.previous_word_start(),
+            Key::UnknownEscSeq(seq) if seq == ['f'] => self.cursor = self.next_word_end(),
+            Key::ArrowUp => self.browse_history(false),
+            Key::ArrowDown => self.browse_history(true),
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Key::Del if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            Key::Char('\x17') => self.delete_previous_word(),
+            Key::UnknownEscSeq(seq) if seq == ['\x7f'] => self.delete_previous_word(),
+            Key::Char('\x15') => {
+                self.chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Key::Char('\x0b') => self.chars.truncate(self.cursor),
+            Key::Char(c) if !c.is_control() => {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Reads the previous edits from `history_file`, oldest first.
+fn load_history(history_file: &Path) -> Vec<String> {
+    fs::read_to_string(history_file)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Adds `script` at the end of the history in `history_file`, keeping only the last
+/// `MAX_HISTORY_SIZE` edits.
+fn save_history(history_file: &Path, script: &str) -> std::io::Result<()> {
+    let mut history = load_history(history_file);
+    // Multi-line scripts cannot be stored one per line
+    if script.contains('\n') || history.last().map(String::as_str) == Some(script) {
+        return Ok(());
+    }
+    history.push(script.to_owned());
+    let start = history.len().saturating_sub(MAX_HISTORY_SIZE);
+    if let Some(parent) = history_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(history_file, history[start..].join("\n") + "\n")
+}
+
+/// Edits `text` on the current line of `term`, until it is confirmed or cancelled.
+///
+/// `↑` and `↓` browse the commands edited before, stored in `~/.cache/ohcrab`. The
+/// line is cleared before returning. A multi-line script cannot be edited on a single
+/// line, so its edition is cancelled right away.
+pub fn edit_line(term: &Term, text: &str) -> EditOutcome {
+    if text.contains('\n') {
+        logger::debug("Multi-line scripts cannot be edited");
+        return EditOutcome::Cancel;
+    }
+    let history_file = get_cache_dir().join(HISTORY_FILE_NAME);
+    let mut editor = LineEditor::new(text, load_history(&history_file));
+    let prompt_width = measure_text_width(PROMPT);
+
+    let mut cursor_row = 0;
+    let outcome = loop {
+        // Back to the start of the prompt, the previous line may have wrapped
+        let _ = term.move_cursor_up(cursor_row);
+        let _ = term.clear_to_end_of_screen();
+        let _ = term.write_str(&format!(
+            "{}{}",
+            style(PROMPT).for_stderr().cyan(),
+            editor.text()
+        ));
+        let layout = editor.layout(prompt_width, terminal_size(term).1.max(1));
+        // The terminal only moves to the next row with the next character
+        if layout.end_column == 0 && layout.end_row > 0 {
+            let _ = term.write_str("\r\n");
+        }
+        let _ = term.move_cursor_up(layout.end_row - layout.cursor_row);
+        let _ = term.write_str("\r");
+        let _ = term.move_cursor_right(layout.cursor_column);
+        cursor_row = layout.cursor_row;
+
+        match term.read_key() {
+            Ok(key) => {
+                if let Some(outcome) = editor.handle_key(key) {
+                    break outcome;
+                }
+            }
+            Err(_) => break EditOutcome::Abort,
+        }
+    };
+
+    let _ = term.move_cursor_up(cursor_row);
+    let _ = term.clear_to_end_of_screen();
+    if let EditOutcome::Accept(script) = &outcome {
+        if let Err(error) = save_history(&history_file, script) {
+            logger::debug(&format!("Cannot save the edit history: {error}"));
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_history, save_history, EditOutcome, Layout, LineEditor, MAX_HISTORY_SIZE};
+    use console::Key;
+    use rstest::rstest;
+
+    fn type_keys(editor: &mut LineEditor, keys: Vec<Key>) -> Option<EditOutcome> {
+        keys.into_iter().find_map(|key| editor.handle_key(key))
+    }
+
+    #[rstest]
+    #[case(vec![Key::Char('s')], "git pushs", 9)]
+    #[case(vec![Key::Backspace, Key::Backspace], "git pu", 6)]
+    #[case(vec![Key::Home, Key::Del, Key::Char('G')], "Git push", 1)]
+    #[case(vec![Key::ArrowLeft, Key::ArrowLeft, Key::Char('x')], "git puxsh", 7)]
+    #[case(vec![Key::ArrowRight, Key::Char('\x01'), Key::Char('\x05'), Key::Char('!')], "git push!", 9)]
+    #[case(vec![Key::Char('\x17')], "git ", 4)]
+    #[case(vec![Key::Char(' '), Key::Char(' '), Key::Char('\x17')], "git ", 4)]
+    #[case(vec![Key::ArrowLeft, Key::Char('\x17')], "git h", 4)]
+    #[case(vec![Key::UnknownEscSeq(vec!['\x7f'])], "git ", 4)]
+    #[case(vec![Key::ArrowLeft, Key::Char('\x15')], "h", 0)]
+    #[case(vec![Key::ArrowLeft, Key::Char('\x0b')], "git pus", 7)]
+    #[case(vec![Key::UnknownEscSeq(vec!['b']), Key::Char('-')], "git -push", 5)]
+    #[case(vec![Key::Home, Key::UnknownEscSeq(vec!['f']), Key::Char('!')], "git! push", 4)]
+    #[case(vec![Key::Home, Key::Backspace, Key::End, Key::Del], "git push", 8)]
+    #[case(vec![Key::Char('\t'), Key::Tab], "git push", 8)]
+    fn test_handle_key(#[case] keys: Vec<Key>, #[case] text: &str, #[case] cursor: usize) {
+        let mut editor = LineEditor::new("git push", vec![]);
+        assert_eq!(type_keys(&mut editor, keys), None);
+        assert_eq!(editor.text(), text);
+        assert_eq!(editor.cursor, cursor);
+    }
+
+    #[test]
+    fn test_handle_key_outcome() {
+        let mut editor = LineEditor::new("git push", vec![]);
+        assert_eq!(
+            type_keys(&mut editor, vec![Key::Char('\x17'), Key::Enter]),
+            Some(EditOutcome::Accept("git ".to_owned()))
+        );
+        // An empty line cannot be run
+        assert_eq!(
+            type_keys(&mut editor, vec![Key::Char('\x15'), Key::Enter]),
+            None
+        );
+        assert_eq!(
+            type_keys(&mut editor, vec![Key::Escape]),
+            Some(EditOutcome::Cancel)
+        );
+    }
+
+    #[rstest]
+    // "✎ git push" fits on the first row, the cursor at its end
+    #[case("git push", 8, 80, (0, 10, 0, 10))]
+    #[case("git push", 4, 80, (0, 10, 0, 6))]
+    // Wraps after 6 columns: the line ends at the start of the third row
+    #[case("git push", 8, 6, (1, 4, 1, 4))]
+    #[case("git push", 3, 6, (1, 4, 0, 5))]
+    #[case("git push", 4, 6, (1, 4, 1, 0))]
+    #[case("echo 1", 6, 4, (2, 0, 2, 0))]
+    // Wide characters take two columns
+    #[case("echo 日本", 7, 80, (0, 11, 0, 11))]
+    #[case("echo 日本", 5, 6, (1, 5, 1, 1))]
+    fn test_layout(
+        #[case] text: &str,
+        #[case] cursor: usize,
+        #[case] term_width: usize,
+        #[case] expected: (usize, usize, usize, usize),
+    ) {
+        let mut editor = LineEditor::new(text, vec![]);
+        editor.cursor = cursor;
+        let (end_row, end_column, cursor_row, cursor_column) = expected;
+        assert_eq!(
+            editor.layout(2, term_width),
+            Layout {
+                end_row,
+                end_column,
+                cursor_row,
+                cursor_column,
+            }
+        );
+    }
+
+    #[test]
+    fn test_browse_history() {
+        let history = vec!["ls -l".to_owned(), "git status".to_owned()];
+        let mut editor = LineEditor::new("git push", history);
+
+        type_keys(&mut editor, vec![Key::Char('!'), Key::ArrowUp]);
+        assert_eq!(editor.text(), "git status");
+        assert_eq!(editor.cursor, 10);
+        type_keys(&mut editor, vec![Key::ArrowUp, Key::ArrowUp]);
+        assert_eq!(editor.text(), "ls -l");
+        // The line being edited keeps its changes
+        type_keys(
+            &mut editor,
+            vec![Key::ArrowDown, Key::ArrowDown, Key::ArrowDown],
+        );
+        assert_eq!(editor.text(), "git push!");
+    }
+
+    #[test]
+    fn test_save_history() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let history_file = cache_dir.path().join("ohcrab").join("edit_history");
+        assert!(load_history(&history_file).is_empty());
+
+        save_history(&history_file, "git status").unwrap();
+        save_history(&history_file, "ls -l").unwrap();
+        save_history(&history_file, "ls -l").unwrap();
+        save_history(&history_file, "echo a\necho b").unwrap();
+        assert_eq!(load_history(&history_file), vec!["git status", "ls -l"]);
+
+        for i in 0..MAX_HISTORY_SIZE {
+            save_history(&history_file, &format!("echo {i}")).unwrap();
+        }
+        let history = load_history(&history_file);
+        assert_eq!(history.len(), MAX_HISTORY_SIZE);
+        assert_eq!(history[0], "echo 0");
+    }
+}
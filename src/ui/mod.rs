@@ -0,0 +1,405 @@
+mod line_editor;
+
+use crate::cli::command::CorrectedCommand;
+use console::{style, truncate_str, Key, Term};
+use line_editor::{edit_line, EditOutcome};
+use std::borrow::Cow;
+
+/// Maximum number of commands shown at once, the list scrolls to show the others.
+const MAX_VISIBLE_ITEMS: usize = 10;
+
+/// Formats the row of a command in the menu.
+///
+/// # Arguments
+///
+/// * `number` - Number of the command in the list, starting from 1.
+/// * `command` - A reference to a `CorrectedCommand`.
+/// * `selected` - Whether the command is the one currently selected.
+/// * `show_rules` - Whether to display all the rules that proposed the command, or
+///   only the first one.
+fn format_row(
+    number: usize,
+    command: &CorrectedCommand,
+    selected: bool,
+    show_rules: bool,
+) -> String {
+    let marker = if selected {
+        style("➜").for_stderr().green().bold().to_string()
+    } else {
+        " ".to_owned()
+    };
+    // A multi-line script is kept on one row
+    let script = command.script.replace('\n', "\\n");
+    let script = if selected {
+        style(script).for_stderr().bold().to_string()
+    } else {
+        script
+    };
+    let rules = if show_rules {
+        command.rule_names.join(", ")
+    } else {
+        command.rule_name.to_owned()
+    };
+    let side_effect = if command.side_effect.is_some() {
+        format!(" {}", style("+side effect").for_stderr().yellow())
+    } else {
+        "".to_owned()
+    };
+    format!(
+        "{marker} {number}. {script} {}{side_effect}",
+        style(format!("[{rules}]")).for_stderr().dim()
+    )
+}
+
+/// Formats the line listing the keys of the menu.
+fn format_help(index: usize, num_items: usize, num_visible: usize) -> String {
+    let position = if num_visible < num_items {
+        format!("({}/{}) ", index + 1, num_items)
+    } else {
+        "".to_owned()
+    };
+    format!(
+        "  {position}[{}|{}|{}|{}|{}|{}|{}]",
+        style("enter").for_stderr().green(),
+        style("↑/k").for_stderr().blue(),
+        style("↓/j").for_stderr().blue(),
+        style("1-9").for_stderr().blue(),
+        style("e").for_stderr().blue(),
+        style("r").for_stderr().blue(),
+        style("esc").for_stderr().red()
+    )
+}
+
+/// Returns the index of the first visible command, scrolling from `first_visible`
+/// as little as possible to show the command at `index`.
+fn scroll_to(index: usize, first_visible: usize, num_visible: usize) -> usize {
+    if index < first_visible {
+        index
+    } else if index >= first_visible + num_visible {
+        index + 1 - num_visible
+    } else {
+        first_visible
+    }
+}
+
+/// Formats the lines of the menu: the visible commands and the keys. The lines are
+/// truncated to `width` columns, so each of them takes a single row of the terminal
+/// and the menu can be cleared.
+fn format_menu(
+    corrected_commands: &[CorrectedCommand],
+    index: usize,
+    first_visible: usize,
+    num_visible: usize,
+    show_rules: bool,
+    width: usize,
+) -> Vec<String> {
+    let mut lines: Vec<String> = corrected_commands
+        .iter()
+        .enumerate()
+        .skip(first_visible)
+        .take(num_visible)
+        .map(|(i, command)| format_row(i + 1, command, i == index, show_rules))
+        .collect();
+    lines.push(format_help(index, corrected_commands.len(), num_visible));
+    lines
+        .iter()
+        .map(|line| truncate_str(line, width, "…").into_owned())
+        .collect()
+}
+
+/// What a key does in the menu.
+#[derive(Debug, PartialEq)]
+enum MenuAction {
+    /// Selects the command at this index.
+    Move(usize),
+    /// Runs the command at this index.
+    Run(usize),
+    /// Edits the selected command before running it.
+    Edit,
+    /// Shows (or hides) all the rules that proposed the commands.
+    ToggleRules,
+    Leave,
+    Ignore,
+}
+
+/// Returns what `key` does when the command at `index` is selected.
+fn handle_key(key: Key, index: usize, num_items: usize) -> MenuAction {
+    match key {
+        Key::ArrowUp | Key::Char('k') => MenuAction::Move((index + num_items - 1) % num_items),
+        Key::ArrowDown | Key::Char('j') => MenuAction::Move((index + 1) % num_items),
+        Key::Enter => MenuAction::Run(index),
+        Key::Char(c @ '1'..='9') => {
+            let number = c as usize - '0' as usize;
+            if number <= num_items {
+                MenuAction::Run(number - 1)
+            } else {
+                MenuAction::Ignore
+            }
+        }
+        Key::Char('e') => MenuAction::Edit,
+        Key::Char('r') => MenuAction::ToggleRules,
+        Key::Escape => MenuAction::Leave,
+        _ => MenuAction::Ignore,
+    }
+}
+
+/// Returns the number of rows and columns of `term`.
+///
+/// `Term::size` only works when stdout is a terminal, and it is captured by the shell
+/// function, so the size of the menu's own stream is read instead.
+fn terminal_size(term: &Term) -> (usize, usize) {
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::io::AsRawFd;
+
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // The type of the request depends on the C library
+        #[allow(clippy::useless_conversion)]
+        let result = unsafe { libc::ioctl(term.as_raw_fd(), libc::TIOCGWINSZ.into(), &mut size) };
+        if result == 0 && size.ws_row > 0 && size.ws_col > 0 {
+            return (size.ws_row as usize, size.ws_col as usize);
+        }
+    }
+    let (rows, columns) = term.size();
+    (rows as usize, columns as usize)
+}
+
+/// Ignores SIGINT while it exists, so Ctrl+C is read as a key and the menu can restore
+/// the terminal before exiting.
+#[cfg(target_family = "unix")]
+struct InterruptGuard {
+    previous_handler: libc::sighandler_t,
+}
+
+#[cfg(target_family = "unix")]
+impl InterruptGuard {
+    fn new() -> Self {
+        let previous_handler = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+        Self { previous_handler }
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        unsafe {
+            libc::signal(libc::SIGINT, self.previous_handler);
+        }
+    }
+}
+
+/// Implements an interactive menu for selecting from a list of corrected commands.
+///
+/// The commands are listed with the rule that proposed them, scrolling when they do
+/// not fit. They are selected with the arrows (or `j`/`k`) and `enter`, or directly
+/// with their number. `e` edits the selected command before running it, `r` shows
+/// all the rules of each command. `esc` and `Ctrl+C` leave the menu, the other keys
+/// are ignored.
+///
+/// # Arguments
+///
+/// * `corrected_commands` - A reference to a vector of `CorrectedCommand`.
+///
+/// # Returns
+///
+/// The selected `CorrectedCommand`, or a copy of it without side effect if its
+/// script was edited. `None` if the menu was left.
+pub fn interactive_menu(
+    corrected_commands: &[CorrectedCommand],
+) -> Option<Cow<'_, CorrectedCommand>> {
+    if corrected_commands.is_empty() {
+        return None;
+    }
+
+    let term = Term::stderr();
+    let num_items = corrected_commands.len();
+    // Keeps a line for the keys and one for the prompt
+    let max_visible = terminal_size(&term).0.saturating_sub(2).max(1);
+    let num_visible = num_items.min(MAX_VISIBLE_ITEMS).min(max_visible);
+
+    #[cfg(target_family = "unix")]
+    let _interrupt_guard = InterruptGuard::new();
+    let _ = term.hide_cursor();
+
+    let mut index = 0;
+    let mut first_visible = 0;
+    let mut show_rules = false;
+    let mut num_lines = 0;
+    let selected = loop {
+        first_visible = scroll_to(index, first_visible, num_visible);
+        let _ = term.clear_last_lines(num_lines);
+        let lines = format_menu(
+            corrected_commands,
+            index,
+            first_visible,
+            num_visible,
+            show_rules,
+            terminal_size(&term).1,
+        );
+        let _ = term.write_line(&lines.join("\n"));
+        num_lines = lines.len();
+
+        // Ctrl+C, or the terminal is gone
+        let Ok(key) = term.read_key() else {
+            break None;
+        };
+        match handle_key(key, index, num_items) {
+            MenuAction::Move(new_index) => index = new_index,
+            MenuAction::Run(index) => break corrected_commands.get(index).map(Cow::Borrowed),
+            MenuAction::Edit => {
+                let command = &corrected_commands[index];
+                let _ = term.clear_last_lines(num_lines);
+                num_lines = 0;
+                let _ = term.show_cursor();
+                let outcome = edit_line(&term, &command.script);
+                let _ = term.hide_cursor();
+                match outcome {
+                    EditOutcome::Accept(script) => {
+                        break Some(Cow::Owned(command.with_edited_script(script)))
+                    }
+                    // Back to the menu
+                    EditOutcome::Cancel => {}
+                    EditOutcome::Abort => break None,
+                }
+            }
+            MenuAction::ToggleRules => show_rules = !show_rules,
+            MenuAction::Leave => break None,
+            MenuAction::Ignore => {}
+        }
+    };
+
+    let _ = term.clear_last_lines(num_lines);
+    let _ = term.show_cursor();
+    let selected = selected?;
+    // The selected command stays on screen, the caller ends the line
+    let _ = term.write_str(&style(&selected.script).for_stderr().bold().to_string());
+    Some(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_help, format_menu, format_row, handle_key, scroll_to, MenuAction};
+    use crate::cli::command::{CorrectedCommand, CrabCommand};
+    use console::Key;
+    use rstest::rstest;
+
+    fn side_effect(_: CrabCommand, _: Option<&str>) {}
+
+    fn get_commands(num_commands: usize) -> Vec<CorrectedCommand> {
+        (1..=num_commands)
+            .map(|i| CorrectedCommand::new(format!("command {i}"), None, 1000, "rule".to_owned()))
+            .collect()
+    }
+
+    fn plain(text: &str) -> String {
+        console::strip_ansi_codes(text).into_owned()
+    }
+
+    #[test]
+    fn test_format_row() {
+        let mut command = CorrectedCommand::new(
+            "mkdir -p a/b".to_owned(),
+            Some(side_effect),
+            1000,
+            "mkdir_p".to_owned(),
+        );
+        command.rule_names.push("no_such_file".to_owned());
+
+        assert_eq!(
+            plain(&format_row(1, &command, true, false)),
+            "➜ 1. mkdir -p a/b [mkdir_p] +side effect"
+        );
+        assert_eq!(
+            plain(&format_row(2, &command, false, true)),
+            "  2. mkdir -p a/b [mkdir_p, no_such_file] +side effect"
+        );
+
+        let command = CorrectedCommand::new("ls".to_owned(), None, 1000, "ls_all".to_owned());
+        assert_eq!(
+            plain(&format_row(3, &command, false, false)),
+            "  3. ls [ls_all]"
+        );
+
+        let command = CorrectedCommand::new("ls\npwd".to_owned(), None, 1000, "rule".to_owned());
+        assert_eq!(
+            plain(&format_row(4, &command, false, false)),
+            "  4. ls\\npwd [rule]"
+        );
+    }
+
+    #[test]
+    fn test_format_help() {
+        assert_eq!(
+            plain(&format_help(0, 3, 3)),
+            "  [enter|↑/k|↓/j|1-9|e|r|esc]"
+        );
+        assert_eq!(
+            plain(&format_help(4, 12, 10)),
+            "  (5/12) [enter|↑/k|↓/j|1-9|e|r|esc]"
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0, 0)]
+    #[case(9, 0, 0)]
+    #[case(10, 0, 1)]
+    #[case(11, 1, 2)]
+    #[case(3, 5, 3)]
+    #[case(7, 5, 5)]
+    fn test_scroll_to(#[case] index: usize, #[case] first_visible: usize, #[case] expected: usize) {
+        assert_eq!(scroll_to(index, first_visible, 10), expected);
+    }
+
+    #[test]
+    fn test_format_menu() {
+        let commands = get_commands(5);
+        let lines: Vec<String> = format_menu(&commands, 3, 2, 3, false, 80)
+            .iter()
+            .map(|line| plain(line))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "  3. command 3 [rule]",
+                "➜ 4. command 4 [rule]",
+                "  5. command 5 [rule]",
+                "  (4/5) [enter|↑/k|↓/j|1-9|e|r|esc]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_menu_truncates_lines() {
+        let commands = vec![CorrectedCommand::new(
+            format!("echo {}", "a".repeat(100)),
+            None,
+            1000,
+            "rule".to_owned(),
+        )];
+        let lines = format_menu(&commands, 0, 0, 1, false, 20);
+        assert_eq!(plain(&lines[0]), "➜ 1. echo aaaaaaaaa…");
+        assert_eq!(plain(&lines[1]), "  [enter|↑/k|↓/j|1-…");
+        assert!(lines
+            .iter()
+            .all(|line| console::measure_text_width(line) <= 20));
+    }
+
+    #[rstest]
+    #[case(Key::ArrowDown, 0, MenuAction::Move(1))]
+    #[case(Key::Char('j'), 2, MenuAction::Move(0))]
+    #[case(Key::ArrowUp, 0, MenuAction::Move(2))]
+    #[case(Key::Char('k'), 2, MenuAction::Move(1))]
+    #[case(Key::Enter, 1, MenuAction::Run(1))]
+    #[case(Key::Char('3'), 0, MenuAction::Run(2))]
+    #[case(Key::Char('4'), 0, MenuAction::Ignore)]
+    #[case(Key::Char('9'), 0, MenuAction::Ignore)]
+    #[case(Key::Char('0'), 0, MenuAction::Ignore)]
+    #[case(Key::Char('e'), 1, MenuAction::Edit)]
+    #[case(Key::Char('r'), 1, MenuAction::ToggleRules)]
+    #[case(Key::Escape, 1, MenuAction::Leave)]
+    #[case(Key::Char('x'), 1, MenuAction::Ignore)]
+    #[case(Key::Tab, 1, MenuAction::Ignore)]
+    fn test_handle_key(#[case] key: Key, #[case] index: usize, #[case] expected: MenuAction) {
+        assert_eq!(handle_key(key, index, 3), expected);
+    }
+}
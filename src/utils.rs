@@ -2,35 +2,14 @@ use fuzzt::{algorithms::SequenceMatcher, get_top_n};
 use std::collections::HashSet;
 use std::env;
 
-use std::path::Path;
-
 use crate::cli::command::CrabCommand;
+use crate::config::Settings;
+use crate::executables::get_path_executables;
+use crate::rules::ANSI_SEQUENCE;
 use crate::shell::Shell;
 
 use regex::Regex;
 
-/// This function prints a message to the console when the program is
-/// compiled in debug mode and does nothing in release mode.
-///
-/// # Arguments
-///
-/// * `message` - A string slice that holds the message to be logged.
-///
-/// # Examples
-///
-/// ```
-/// debug_log("This is a debug log message.");
-/// ```
-#[cfg(debug_assertions)]
-pub fn debug_log(message: &str) {
-    println!("{message}");
-}
-
-#[cfg(not(debug_assertions))]
-pub fn debug_log(_: &str) {
-    // This function does nothing when not in debug mode.
-}
-
 /// Replaces an argument in a script.
 ///
 /// This function takes a script and two strings `from_` and `to`. It replaces the last occurrence of `from_` in the script with `to`.
@@ -67,6 +46,7 @@ pub fn replace_argument(script: &str, from_: &str, to: &str) -> String {
 /// * `command` - A reference to a `CrabCommand` that holds the script where the replacement should occur.
 /// * `broken` - The string representing the broken command to be replaced.
 /// * `matched` - A vector of string slices representing the candidate commands for replacement.
+/// * `settings` - User settings, giving the maximum number of close matches.
 ///
 /// # Returns
 ///
@@ -79,11 +59,16 @@ pub fn replace_argument(script: &str, from_: &str, to: &str) -> String {
 /// let command = CrabCommand { script: "echo broken_command" };
 /// let broken = "broken_command";
 /// let matched = vec!["fixed_command1", "fixed_command2"];
-/// let fixed_scripts = replace_command(&command, broken, matched);
+/// let fixed_scripts = replace_command(&command, broken, matched, &Settings::default());
 /// assert_eq!(fixed_scripts, vec!["echo fixed_command1", "echo fixed_command2"]);
 /// ```
-pub fn replace_command(command: &CrabCommand, broken: &str, matched: Vec<&str>) -> Vec<String> {
-    let candidate_commands = get_close_matches(broken, &matched, None, Some(0.2));
+pub fn replace_command(
+    command: &CrabCommand,
+    broken: &str,
+    matched: Vec<&str>,
+    settings: &Settings,
+) -> Vec<String> {
+    let candidate_commands = get_close_matches(broken, &matched, settings.num_close_matches, 0.2);
     let mut new_commands = Vec::<String>::new();
     for cmd in candidate_commands {
         new_commands.push(replace_argument(&command.script, broken, cmd.trim()));
@@ -136,6 +121,8 @@ pub fn get_closest<'a>(
 ///
 /// * `word` - The word to find matches for.
 /// * `possibilities` - A slice of strings representing the possibilities.
+/// * `n_matches` - Maximum number of matches, usually `num_close_matches` from the settings.
+/// * `cutoff` - Minimum similarity ratio, usually `close_match_cutoff` from the settings.
 ///
 /// # Returns
 ///
@@ -143,17 +130,14 @@ pub fn get_closest<'a>(
 pub fn get_close_matches<'a>(
     word: &'a str,
     possibilities: &'a [&'a str],
-    n_matches: Option<usize>,
-    cutoff: Option<f64>,
+    n_matches: usize,
+    cutoff: f64,
 ) -> Vec<&'a str> {
-    // TODO: Read parameters from config file
-    let n = n_matches.unwrap_or(3);
-    let cutoff = cutoff.unwrap_or(0.6);
     get_top_n(
         word,
         possibilities,
         Some(cutoff),
-        Some(n),
+        Some(n_matches),
         None,
         Some(&SequenceMatcher),
     )
@@ -170,34 +154,27 @@ pub fn get_alias() -> String {
 
 /// Gets a vector of all executables in the PATH excluding certain entry points.
 ///
+/// The executables are listed once per process, from the index cached on disk (see
+/// `executables::get_path_executables`).
+///
+/// # Arguments
+///
+/// * `system_shell` - If given, the aliases defined in the shell are included as well.
+///
 /// # Returns
 ///
 /// A vector of executable names.
-pub fn get_all_executable() -> Vec<String> {
+pub fn get_all_executable(system_shell: Option<&dyn Shell>) -> Vec<String> {
     let tf_entry_points = ["ohcrab", "crab"];
 
-    let mut bins = vec![];
-
-    if let Ok(path_var) = env::var("PATH") {
-        for path in env::split_paths(&path_var) {
-            if let Ok(iterdir) = Path::new(&path).read_dir() {
-                for executable in iterdir.flatten() {
-                    if let Ok(file_type) = executable.file_type() {
-                        if !file_type.is_dir() {
-                            if let Some(file_name) = executable.path().file_name() {
-                                if let Some(name) = file_name.to_str() {
-                                    if !tf_entry_points.contains(&name) {
-                                        bins.push(name.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    let mut bins: Vec<String> = get_path_executables()
+        .iter()
+        .filter(|name| !tf_entry_points.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    if let Some(system_shell) = system_shell {
+        bins.extend(system_shell.get_aliases().into_keys());
     }
-    // TODO: Add shell aliases
     bins
 }
 
@@ -239,6 +216,7 @@ fn not_corrected(history: &Vec<String>, oc_alias: &String) -> Vec<String> {
 ///
 /// * `command` - A reference to the current CrabCommand.
 /// * `system_shell` - A reference to the system shell.
+/// * `settings` - User settings, limiting the number of history entries read.
 ///
 /// # Returns
 ///
@@ -246,14 +224,19 @@ fn not_corrected(history: &Vec<String>, oc_alias: &String) -> Vec<String> {
 pub fn get_valid_history_without_current(
     command: &CrabCommand,
     system_shell: &dyn Shell,
+    settings: &Settings,
 ) -> Vec<String> {
     let mut corrected: Vec<String> = Vec::new();
     let mut valid_history: Vec<String> = Vec::new();
 
-    let history = system_shell.get_history(None);
+    let mut history = system_shell.get_history(None);
+    if let Some(limit) = settings.history_limit {
+        // Keep only the most recent entries
+        history.drain(..history.len().saturating_sub(limit));
+    }
     let mut executables = history.clone();
     executables.extend(system_shell.get_builtin_commands());
-    executables.extend(get_all_executable());
+    executables.extend(get_all_executable(Some(system_shell)));
     let executables: HashSet<_> = executables.into_iter().collect();
 
     for line in not_corrected(&history, &get_alias()) {
@@ -312,14 +295,21 @@ pub fn get_all_matched_commands(stderr: &str, separator_option: Option<Vec<&str>
     matched_commands
 }
 
+/// Removes the ANSI escape sequences (colors, cursor moves, terminal titles) from
+/// the output of a command, so the rules only see its text.
+pub fn strip_ansi_sequences(text: &str) -> String {
+    ANSI_SEQUENCE.replace_all(text, "").into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use mockall::mock;
 
-    use crate::{cli::command::CrabCommand, shell::Shell, utils::get_alias};
+    use crate::{cli::command::CrabCommand, config::Settings, shell::Shell, utils::get_alias};
 
     use super::get_all_matched_commands;
     use super::get_valid_history_without_current;
+    use super::strip_ansi_sequences;
     use rstest::rstest;
 
     mock! {
@@ -353,7 +343,15 @@ mod tests {
 
         assert_eq!(
             vec!["command1", "cmp a.txt b.txt"],
-            get_valid_history_without_current(&command, &*system_shell)
+            get_valid_history_without_current(&command, &*system_shell, &Settings::default())
+        );
+        let settings = Settings {
+            history_limit: Some(1),
+            ..Settings::default()
+        };
+        assert_eq!(
+            vec!["cmp a.txt b.txt"],
+            get_valid_history_without_current(&command, &*system_shell, &settings)
         );
 
         let mut mock_shell = MockMyShell::new();
@@ -371,7 +369,7 @@ mod tests {
         // Skip "cmp a.txt b.txt" because it comes before "crab" (alias)
         assert_eq!(
             Vec::<String>::new(),
-            get_valid_history_without_current(&command, &*system_shell)
+            get_valid_history_without_current(&command, &*system_shell, &Settings::default())
         );
     }
 
@@ -382,4 +380,13 @@ mod tests {
     fn test_get_all_matched_commands(#[case] stderr: &str, #[case] result: Vec<&str>) {
         assert_eq!(get_all_matched_commands(stderr, None), result);
     }
+
+    #[rstest]
+    #[case("\x1b[01;31merror\x1b[0m: no such file", "error: no such file")]
+    #[case("\x1b]0;title\x07\x1b[?2004hls", "ls")]
+    #[case("\x1b[2K\x1b[1Gdone", "done")]
+    #[case("plain text", "plain text")]
+    fn test_strip_ansi_sequences(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(strip_ansi_sequences(text), expected);
+    }
 }
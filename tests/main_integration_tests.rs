@@ -36,8 +36,155 @@ fn test_debug_output() {
         .arg("brnch")
         .assert()
         .success()
-        .stdout(
+        .stdout(predicate::str::contains("git branch"))
+        .stdout(predicate::str::contains("Retrieved command(s):").not())
+        .stderr(
             predicate::str::contains("Retrieved command(s):")
-                .and(predicate::str::contains("git branch")),
+                .and(predicate::str::contains("Command re-run took"))
+                .and(predicate::str::contains("Rule git_not_command took")),
         );
 }
+
+#[test]
+fn test_debug_output_disabled() {
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.args(["--select-first", "--", "git", "brnch"])
+        .env_remove("OHCRAB_DEBUG")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Retrieved command(s):").not());
+}
+
+#[test]
+fn test_debug_log_file() {
+    let log_dir = tempfile::tempdir().unwrap();
+    let log_file = log_dir.path().join("ohcrab.log");
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.args(["--select-first", "--", "git", "brnch"])
+        .env("OHCRAB_DEBUG", "1")
+        .env("OHCRAB_LOG_FILE", &log_file)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Retrieved command(s):").not());
+
+    let log = std::fs::read_to_string(log_file).unwrap();
+    assert!(log.contains("[ohcrab DEBUG"));
+    assert!(log.contains("Retrieved command(s):"));
+}
+
+#[test]
+fn test_user_rule() {
+    let config_dir = tempfile::tempdir().unwrap();
+    let rules_dir = config_dir.path().join("rules");
+    std::fs::create_dir(&rules_dir).unwrap();
+    std::fs::write(
+        rules_dir.join("echo_typo.toml"),
+        r#"
+        apps = ["echo"]
+        patterns = ["did you mean '(?P<right>[^']+)'"]
+        replacement = "{script_parts[0]} {right}"
+        priority = 1
+        "#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.env("OHCRAB_CONFIG_DIR", config_dir.path())
+        .arg("--select-first")
+        .arg("--")
+        .arg("echo")
+        .arg("\"did you mean 'fixed_by_user_rule'\"")
+        // Successful commands are not corrected
+        .arg("&& false")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("echo fixed_by_user_rule"));
+}
+
+#[test]
+fn test_successful_command() {
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.arg("--select-first")
+        .arg("--")
+        .arg("echo")
+        .arg("hello")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "`echo hello` ran successfully, nothing to correct",
+        ));
+}
+
+#[test]
+fn test_json_output() {
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    let output = cmd
+        .arg("--format")
+        .arg("json")
+        .arg("--")
+        .arg("git")
+        .arg("brnch")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let candidates: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let git_branch = candidates
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|candidate| candidate["script"] == "git branch")
+        .unwrap();
+    assert_eq!(git_branch["rule"], "git_not_command");
+    assert_eq!(git_branch["rules"][0], "git_not_command");
+    assert_eq!(git_branch["side_effect"], false);
+    assert!(git_branch["priority"].is_u64());
+}
+
+#[test]
+fn test_rules_list() {
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.arg("rules")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("NAME "))
+        .stdout(predicate::str::contains("git_not_command "));
+}
+
+#[test]
+fn test_rules_explain() {
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.args(["rules", "explain", "sudo", "--output", "Permission denied"])
+        .args(["--", "ls", "/root"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Match: yes"))
+        .stdout(predicate::str::contains("  sudo ls /root (priority: "));
+
+    // Localized messages are translated before matching
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.args(["rules", "explain", "sudo", "--output"])
+        .arg("ls: Verzeichnis '/root' kann nicht geöffnet werden: Keine Berechtigung")
+        .args(["--", "ls", "/root"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Match: yes"));
+
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.args(["rules", "explain", "not_a_rule", "--", "ls"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown rule `not_a_rule`"));
+}
+
+#[test]
+fn test_explain() {
+    let mut cmd = Command::cargo_bin("ohcrab").unwrap();
+    cmd.args(["--explain", "--", "git", "brnch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("git branch").not())
+        .stderr(predicate::str::is_match(r"\ngit_not_command +yes +git branch \(\d+\)\n").unwrap())
+        .stderr(predicate::str::contains("Final ordering:\n  1. git branch"));
+}